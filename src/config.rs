@@ -0,0 +1,8 @@
+//! Settings read from the environment.
+
+use std::env;
+
+/// Whether the variable `name` is set to `1` or `true`, in any case.
+pub fn env_flag(name: &str) -> bool {
+    env::var(name).is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+}
@@ -1,4 +1,7 @@
-use axum::{extract::Path, response::IntoResponse, routing::get, Router};
+use axum::response::IntoResponse;
+
+use crate::extract::Path;
+use crate::registry::{Day, Routes};
 
 async fn sled_id(Path(path): Path<String>) -> impl IntoResponse {
     path.split_terminator('/')
@@ -9,6 +12,12 @@ async fn sled_id(Path(path): Path<String>) -> impl IntoResponse {
         .to_string()
 }
 
-pub fn router() -> Router {
-    Router::new().route("/*path", get(sled_id))
+pub const DAY: Day = Day {
+    prefix: "/1",
+    title: "Cube the bits",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new().get("/*path", sled_id)
 }
@@ -1,25 +1,42 @@
-use axum::{extract::Multipart, response::IntoResponse, routing::post, Router};
-use image::{GenericImageView, ImageReader};
+use axum::{
+    extract::{multipart::MultipartError, Multipart},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use image::{GenericImageView, ImageError, ImageReader};
 use std::io::Cursor;
+use thiserror::Error;
 use tower_http::services::ServeDir;
 
-fn stringify<T: std::fmt::Debug>(x: T) -> String {
-    format!("error code: {:?}", x)
+use crate::error::Problem;
+use crate::registry::{Day, Routes};
+
+#[derive(Error, Debug)]
+enum AppError {
+    #[error("Invalid multipart body: {0}")]
+    Multipart(#[from] MultipartError),
+    #[error("Invalid PNG image: {0}")]
+    Image(#[from] ImageError),
 }
 
-async fn magic_reds(mut multipart: Multipart) -> impl IntoResponse {
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, code) = match self {
+            AppError::Multipart(ref e) => (e.status(), "invalid_multipart"),
+            AppError::Image(_) => (StatusCode::BAD_REQUEST, "invalid_image"),
+        };
+
+        Problem::new(status, code, self.to_string()).into_response()
+    }
+}
+
+async fn magic_reds(mut multipart: Multipart) -> Result<String, AppError> {
     let mut magic_count = 0u32;
-    if let Some(field) = multipart.next_field().await.unwrap() {
-        let _name = field.name().unwrap().to_string();
-        let data = field.bytes().await.unwrap();
+    if let Some(field) = multipart.next_field().await? {
+        let data = field.bytes().await?;
         let mut reader = ImageReader::new(Cursor::new(data));
         reader.set_format(image::ImageFormat::Png);
-        let image = match reader.decode() {
-            Ok(i) => i,
-            Err(e) => {
-                return stringify(e);
-            }
-        };
+        let image = reader.decode()?;
 
         for (_, _, rgb) in image.pixels() {
             if rgb[0] > rgb[1].saturating_add(rgb[2]) {
@@ -28,11 +45,17 @@ async fn magic_reds(mut multipart: Multipart) -> impl IntoResponse {
         }
     }
 
-    format!("{}", magic_count)
+    Ok(format!("{}", magic_count))
 }
 
-pub fn router() -> Router {
-    Router::new()
-        .route("/red_pixels", post(magic_reds))
+pub const DAY: Day = Day {
+    prefix: "/11",
+    title: "Red pixels",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new()
+        .post("/red_pixels", magic_reds)
         .nest_service("/assets", ServeDir::new("assets"))
 }
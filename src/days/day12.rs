@@ -1,145 +1,703 @@
-use std::{ collections::HashMap, sync::Arc };
+mod analytics;
+pub mod clock;
+pub mod store;
+
+use std::{ collections::HashMap, sync::Arc, time::Duration };
 use axum::{
-    extract::{ Path, State },
+    extract::{ FromRef, State },
     http::StatusCode,
     response::{ IntoResponse, Response },
-    routing::{ get, post },
-    Json,
-    Router,
 };
-use jiff::{ tz::TimeZone, Timestamp };
+use jiff::{ tz::TimeZone, SignedDuration, Span, SpanRound, Timestamp, Unit, Zoned };
+use serde::{ Deserialize, Serialize };
 use thiserror::Error;
-use tokio::sync::RwLock;
-use ulid::Ulid;
+use ulid::{ Generator, Ulid };
 use uuid::Uuid;
 
+use crate::config::env_flag;
+use crate::error::Problem;
+use crate::extract::{ Json, Path, Query };
+use crate::registry::{ Day, Routes };
+use analytics::{ DatePredicate, NamedPredicate, PredicateResult };
+use clock::{ Clock, SharedClock, SystemClock, TestClock };
+use store::{ PacketRecord, PacketStore, SharedStore, StoreConfig, StoreError };
+
 #[derive(Error, Debug)]
 enum AppError {
-    #[error("Incorrect ULID")]
-    ParseULIDError,
-    #[error("Incorrect Timestamp")]
-    ParseTimestampError,
+    #[error("Incorrect ULID at index {index}")] ParseULIDError {
+        index: usize,
+    },
+    #[error("Incorrect UUID at index {index}")] ParseUUIDError {
+        index: usize,
+    },
+    #[error("Incorrect Timestamp at index {index}")] ParseTimestampError {
+        index: usize,
+    },
+    /// A single ULID given in the path, so there is no index to report.
+    #[error("Incorrect ULID {0}")] InvalidUlid(String),
+    #[error("Unknown timezone {0}")] UnknownTimeZone(String),
+    #[error("Invalid predicate {name}: {reason}")] InvalidPredicate {
+        name: String,
+        reason: String,
+    },
+    #[error("At most {MAX_GENERATED_ULIDS} ULIDs can be generated at once")]
+    TooManyUlids,
+    #[error("ULID generator overflowed")]
+    GeneratorOverflow,
     #[error("Packet not found")]
     PacketNotFound,
+    /// The `/packets` resource routes' not-found; the legacy `/load` routes
+    /// keep answering 400 with `PacketNotFound`.
+    #[error("Packet not found")]
+    NoSuchPacket,
+    #[error("Invalid TTL")]
+    InvalidTtl,
+    #[error("Elapsed time is out of range")]
+    ElapsedOutOfRange,
+    #[error(transparent)] Store(#[from] StoreError),
+    #[error("Packet store task failed")] StoreTask(#[from] tokio::task::JoinError),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::ParseULIDError => (StatusCode::BAD_REQUEST, "Incorrect ULID".to_string()),
-            AppError::ParseTimestampError =>
-                (StatusCode::BAD_REQUEST, "Incorrect Timestamp".to_string()),
-            AppError::PacketNotFound => (StatusCode::BAD_REQUEST, "Packet not found".to_string()),
+        let (status, code) = match self {
+            AppError::ParseULIDError { .. } => (StatusCode::BAD_REQUEST, "invalid_ulid"),
+            AppError::ParseUUIDError { .. } => (StatusCode::BAD_REQUEST, "invalid_uuid"),
+            AppError::ParseTimestampError { .. } => (StatusCode::BAD_REQUEST, "invalid_timestamp"),
+            AppError::InvalidUlid(_) => (StatusCode::BAD_REQUEST, "invalid_ulid"),
+            AppError::UnknownTimeZone(_) => (StatusCode::BAD_REQUEST, "unknown_time_zone"),
+            AppError::InvalidPredicate { .. } => (StatusCode::BAD_REQUEST, "invalid_predicate"),
+            AppError::TooManyUlids => (StatusCode::BAD_REQUEST, "too_many_ulids"),
+            AppError::GeneratorOverflow =>
+                (StatusCode::INTERNAL_SERVER_ERROR, "generator_overflow"),
+            AppError::PacketNotFound => (StatusCode::BAD_REQUEST, "packet_not_found"),
+            AppError::NoSuchPacket => (StatusCode::NOT_FOUND, "packet_not_found"),
+            AppError::InvalidTtl => (StatusCode::BAD_REQUEST, "invalid_ttl"),
+            AppError::ElapsedOutOfRange =>
+                (StatusCode::INTERNAL_SERVER_ERROR, "elapsed_out_of_range"),
+            AppError::Store(_) | AppError::StoreTask(_) =>
+                (StatusCode::INTERNAL_SERVER_ERROR, "packet_store"),
         };
 
-        (status, error_message).into_response()
+        let problem = Problem::new(status, code, self.to_string());
+        match self {
+            AppError::ParseULIDError { index }
+            | AppError::ParseUUIDError { index }
+            | AppError::ParseTimestampError { index } => problem.with("index", index),
+            AppError::InvalidPredicate { name, .. } => problem.with("predicate", name),
+            _ => problem,
+        }.into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SaveOptions {
+    /// Seconds until the packet expires; packets without a TTL live forever.
+    ttl: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BulkSave {
+    packet: String,
+    ttl: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pagination {
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct PacketPage {
+    total: usize,
+    offset: usize,
+    limit: usize,
+    packets: Vec<PacketRecord>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DurationUnit {
+    Ms,
+    #[default]
+    S,
+    Human,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LoadFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Deserialize)]
+struct LoadOptions {
+    #[serde(default)]
+    unit: DurationUnit,
+    /// Bulk loads always answer in JSON and ignore this.
+    #[serde(default)]
+    format: LoadFormat,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Elapsed {
+    Count(i64),
+    Human(String),
+}
+
+impl Elapsed {
+    fn new(duration: SignedDuration, unit: DurationUnit) -> Result<Self, AppError> {
+        Ok(match unit {
+            DurationUnit::Ms => Elapsed::Count(duration.as_millis() as i64),
+            DurationUnit::S => Elapsed::Count(duration.as_secs()),
+            DurationUnit::Human => Elapsed::Human(format!("{:#}", elapsed_span(duration)?)),
+        })
     }
 }
 
-type PacketMap = Arc<RwLock<HashMap<String, Timestamp>>>;
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Elapsed::Count(count) => write!(f, "{count}"),
+            Elapsed::Human(human) => write!(f, "{human}"),
+        }
+    }
+}
+
+/// Balances a duration into hours, minutes and seconds at millisecond precision.
+fn elapsed_span(duration: SignedDuration) -> Result<Span, AppError> {
+    Span::try_from(duration)
+        .and_then(|span| {
+            span.round(SpanRound::new().largest(Unit::Hour).smallest(Unit::Millisecond))
+        })
+        .map_err(|_| AppError::ElapsedOutOfRange)
+}
+
+#[derive(Debug, Serialize)]
+struct LoadedPacket {
+    packet: String,
+    saved: Timestamp,
+    now: Timestamp,
+    /// ISO 8601 duration, e.g. `PT1m3.5s`.
+    duration: String,
+    elapsed: Elapsed,
+}
+
+impl LoadedPacket {
+    fn new(record: PacketRecord, now: Timestamp, unit: DurationUnit) -> Result<Self, AppError> {
+        let duration = record.saved.duration_until(now);
+        Ok(LoadedPacket {
+            packet: record.packet,
+            saved: record.saved,
+            now,
+            duration: elapsed_span(duration)?.to_string(),
+            elapsed: Elapsed::new(duration, unit)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BulkLoaded {
+    packet: String,
+    elapsed: Option<Elapsed>,
+}
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 1000;
+const EVICTION_INTERVAL: Duration = Duration::from_secs(1);
+
+fn new_record(packet: String, ttl: Option<u64>, now: Timestamp) -> Result<PacketRecord, AppError> {
+    let expires = match ttl {
+        Some(secs) => {
+            let ttl = SignedDuration::from_secs(i64::try_from(secs).unwrap_or(i64::MAX));
+            Some(now.checked_add(ttl).map_err(|_| AppError::InvalidTtl)?)
+        }
+        None => None,
+    };
+    Ok(PacketRecord { packet, saved: now, expires })
+}
+
+/// Loads a packet, treating records past their TTL as already evicted.
+fn load_live(store: &SharedStore, packet: &str, now: Timestamp) -> Result<Option<PacketRecord>, AppError> {
+    Ok(store.load(packet)?.filter(|record| !record.is_expired(now)))
+}
+
+/// Runs a store write on the blocking pool, since a file store syncs to disk.
+async fn write<T: Send + 'static>(
+    store: &SharedStore,
+    op: impl FnOnce(&dyn PacketStore) -> Result<T, StoreError> + Send + 'static
+) -> Result<T, AppError> {
+    let store = store.clone();
+    Ok(tokio::task::spawn_blocking(move || op(store.as_ref())).await??)
+}
 
 async fn save_packet(
     Path(packet): Path<String>,
-    State(packet_map_state): State<PacketMap>
+    Query(options): Query<SaveOptions>,
+    State(store): State<SharedStore>,
+    State(clock): State<SharedClock>
 ) -> Result<impl IntoResponse, AppError> {
-    let mut packet_map = packet_map_state.write().await;
-    packet_map.insert(packet, Timestamp::now());
-    Ok(format!("{:?}", packet_map.clone()))
+    let record = new_record(packet, options.ttl, clock.now())?;
+    let saved = record.clone();
+    write(&store, move |store| store.save(saved)).await?;
+    Ok(Json(record))
 }
 
 async fn load_packet(
     Path(packet): Path<String>,
-    State(packet_map_state): State<PacketMap>
-) -> Result<impl IntoResponse, AppError> {
-    let packet_map = packet_map_state.read().await;
-    let timestamp = match packet_map.get(&packet) {
-        Some(ts) => ts,
+    Query(options): Query<LoadOptions>,
+    State(store): State<SharedStore>,
+    State(clock): State<SharedClock>
+) -> Result<Response, AppError> {
+    let now = clock.now();
+    let record = match load_live(&store, &packet, now)? {
+        Some(record) => record,
         None => {
             return Err(AppError::PacketNotFound);
         }
     };
-    let duration = timestamp.duration_until(Timestamp::now());
-    Ok(format!("{}", duration.as_secs()))
+    let loaded = LoadedPacket::new(record, now, options.unit)?;
+    match options.format {
+        LoadFormat::Text => Ok(loaded.elapsed.to_string().into_response()),
+        LoadFormat::Json => Ok(Json(loaded).into_response()),
+    }
+}
+
+async fn save_packets(
+    State(store): State<SharedStore>,
+    State(clock): State<SharedClock>,
+    Json(packets): Json<Vec<BulkSave>>
+) -> Result<impl IntoResponse, AppError> {
+    let now = clock.now();
+    let records = packets
+        .into_iter()
+        .map(|BulkSave { packet, ttl }| new_record(packet, ttl, now))
+        .collect::<Result<Vec<_>, _>>()?;
+    let saved = records.clone();
+    write(&store, move |store| store.save_all(saved)).await?;
+    Ok(Json(records))
+}
+
+async fn load_packets(
+    Query(options): Query<LoadOptions>,
+    State(store): State<SharedStore>,
+    State(clock): State<SharedClock>,
+    Json(packets): Json<Vec<String>>
+) -> Result<impl IntoResponse, AppError> {
+    let now = clock.now();
+    let mut loaded = Vec::with_capacity(packets.len());
+    for packet in packets {
+        let elapsed = load_live(&store, &packet, now)?
+            .map(|record| Elapsed::new(record.saved.duration_until(now), options.unit))
+            .transpose()?;
+        loaded.push(BulkLoaded { packet, elapsed });
+    }
+    Ok(Json(loaded))
+}
+
+/// Lists the packets that `get_packet` would find, hiding expired ones that
+/// haven't been evicted yet.
+async fn list_packets(
+    Query(pagination): Query<Pagination>,
+    State(store): State<SharedStore>,
+    State(clock): State<SharedClock>
+) -> Result<impl IntoResponse, AppError> {
+    let limit = pagination.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    let (packets, total) = store.list(clock.now(), pagination.offset, limit)?;
+    Ok(Json(PacketPage { total, offset: pagination.offset, limit, packets }))
+}
+
+async fn get_packet(
+    Path(packet): Path<String>,
+    State(store): State<SharedStore>,
+    State(clock): State<SharedClock>
+) -> Result<impl IntoResponse, AppError> {
+    match load_live(&store, &packet, clock.now())? {
+        Some(record) => Ok(Json(record)),
+        None => Err(AppError::NoSuchPacket),
+    }
+}
+
+async fn delete_packet(
+    Path(packet): Path<String>,
+    State(store): State<SharedStore>,
+    State(clock): State<SharedClock>
+) -> Result<impl IntoResponse, AppError> {
+    let now = clock.now();
+    if write(&store, move |store| store.delete(&packet, now)).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NoSuchPacket)
+    }
+}
+
+/// Periodically drops expired packets so the store doesn't grow without bound.
+///
+/// The task only holds a weak reference and stops once the router that owns
+/// the store is dropped.
+fn spawn_eviction(store: &SharedStore, clock: SharedClock) {
+    let store = Arc::downgrade(store);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EVICTION_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(store) = store.upgrade() else {
+                break;
+            };
+            let now = clock.now();
+            if let Err(e) = write(&store, move |store| store.evict_expired(now)).await {
+                tracing::error!(error = %e, "day12 packet eviction failed");
+            }
+        }
+    });
+}
+
+fn parse_ulids(ulids: &[String]) -> Result<Vec<Ulid>, AppError> {
+    ulids
+        .iter()
+        .enumerate()
+        .map(|(index, ulid)| Ulid::from_string(ulid).map_err(|_| AppError::ParseULIDError { index }))
+        .collect()
+}
+
+fn ulid_timestamp(ulid: &Ulid, index: usize) -> Result<Timestamp, AppError> {
+    Timestamp::from_millisecond(ulid.timestamp_ms() as i64).map_err(|_| {
+        AppError::ParseTimestampError { index }
+    })
 }
 
 async fn ulid_to_uuid(Json(ulids): Json<Vec<String>>) -> Result<impl IntoResponse, AppError> {
-    let mut temp_vec: Vec<Uuid> = Vec::new();
-    for ulid_string in &ulids {
-        let ulid: Ulid = match Ulid::from_string(ulid_string) {
+    let mut temp_vec: Vec<Uuid> = parse_ulids(&ulids)?.into_iter().map(Uuid::from).collect();
+    temp_vec.reverse();
+    Ok(serde_json::to_string(&temp_vec).unwrap())
+}
+
+async fn uuid_to_ulid(Json(uuids): Json<Vec<String>>) -> Result<impl IntoResponse, AppError> {
+    let mut temp_vec: Vec<Ulid> = Vec::new();
+    for (index, uuid_string) in uuids.iter().enumerate() {
+        let uuid = match Uuid::parse_str(uuid_string) {
             Ok(u) => u,
             Err(_) => {
-                return Err(AppError::ParseULIDError);
+                return Err(AppError::ParseUUIDError { index });
             }
         };
-        let uuid: Uuid = ulid.into();
-        temp_vec.push(uuid);
+        temp_vec.push(uuid.into());
     }
-    temp_vec.reverse();
-    Ok(serde_json::to_string(&temp_vec).unwrap())
+    Ok(Json(temp_vec))
+}
+
+#[derive(Debug, Deserialize)]
+struct GenerateOptions {
+    #[serde(default = "default_generate_count")]
+    count: usize,
+}
+
+fn default_generate_count() -> usize {
+    1
+}
+
+const MAX_GENERATED_ULIDS: usize = 10_000;
+
+/// Generates ULIDs that sort strictly after each other, even within one millisecond.
+async fn generate_ulids(
+    Query(options): Query<GenerateOptions>,
+    State(clock): State<SharedClock>
+) -> Result<impl IntoResponse, AppError> {
+    if options.count > MAX_GENERATED_ULIDS {
+        return Err(AppError::TooManyUlids);
+    }
+    let mut generator = Generator::new();
+    let mut temp_vec: Vec<Ulid> = Vec::with_capacity(options.count);
+    for _ in 0..options.count {
+        let ulid = generator
+            .generate_from_datetime(clock.now().into())
+            .map_err(|_| AppError::GeneratorOverflow)?;
+        temp_vec.push(ulid);
+    }
+    Ok(Json(temp_vec))
+}
+
+#[derive(Debug, Serialize)]
+struct DecodedUlid {
+    ulid: Ulid,
+    uuid: Uuid,
+    timestamp_ms: u64,
+    timestamp: Timestamp,
+    /// The 80 random bits as 20 hex digits.
+    random: String,
+}
+
+async fn decode_ulid(Path(ulid): Path<String>) -> Result<impl IntoResponse, AppError> {
+    let invalid = || AppError::InvalidUlid(ulid.clone());
+    let ulid = Ulid::from_string(&ulid).map_err(|_| invalid())?;
+    let timestamp = Timestamp::from_millisecond(ulid.timestamp_ms() as i64).map_err(|_| invalid())?;
+    Ok(
+        Json(DecodedUlid {
+            ulid,
+            uuid: ulid.into(),
+            timestamp_ms: ulid.timestamp_ms(),
+            timestamp,
+            random: format!("{:020x}", ulid.random()),
+        })
+    )
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Deserialize)]
+struct SortOptions {
+    #[serde(default)]
+    order: SortOrder,
+    /// Inclusive lower bound on the embedded time.
+    from: Option<Timestamp>,
+    /// Exclusive upper bound on the embedded time.
+    to: Option<Timestamp>,
+}
+
+async fn sort_ulids(
+    Query(options): Query<SortOptions>,
+    Json(ulids): Json<Vec<String>>
+) -> Result<impl IntoResponse, AppError> {
+    let mut temp_vec: Vec<Ulid> = Vec::new();
+    for (index, ulid) in parse_ulids(&ulids)?.into_iter().enumerate() {
+        let time = ulid_timestamp(&ulid, index)?;
+        let after_from = options.from.is_none_or(|from| time >= from);
+        let before_to = options.to.is_none_or(|to| time < to);
+        if after_from && before_to {
+            temp_vec.push(ulid);
+        }
+    }
+    temp_vec.sort();
+    if let SortOrder::Desc = options.order {
+        temp_vec.reverse();
+    }
+    Ok(Json(temp_vec))
+}
+
+#[derive(Debug, Deserialize)]
+struct TimeZoneOptions {
+    /// IANA timezone name, e.g. `Europe/Oslo`; defaults to UTC.
+    tz: Option<String>,
+}
+
+fn time_zone(name: Option<&str>) -> Result<TimeZone, AppError> {
+    match name {
+        Some(name) => TimeZone::get(name).map_err(|_| AppError::UnknownTimeZone(name.to_string())),
+        None => Ok(TimeZone::UTC),
+    }
+}
+
+fn validate_predicates(predicates: &[NamedPredicate]) -> Result<(), AppError> {
+    predicates
+        .iter()
+        .try_for_each(NamedPredicate::validate)
+        .map_err(|(name, reason)| AppError::InvalidPredicate { name, reason })
+}
+
+fn zoned_ulids(ulids: &[String], tz: &TimeZone) -> Result<Vec<(Ulid, Zoned)>, AppError> {
+    let mut temp_vec = Vec::with_capacity(ulids.len());
+    for (index, ulid) in parse_ulids(ulids)?.into_iter().enumerate() {
+        let time = ulid_timestamp(&ulid, index)?;
+        temp_vec.push((ulid, time.to_zoned(tz.clone())));
+    }
+    Ok(temp_vec)
 }
 
 async fn ulid_to_dates(
     Path(weekday): Path<i8>,
+    State(clock): State<SharedClock>,
+    Query(options): Query<TimeZoneOptions>,
     Json(ulids): Json<Vec<String>>
 ) -> Result<impl IntoResponse, AppError> {
-    let mut temp_map: HashMap<&str, u32> = HashMap::from([
-        ("christmas eve", 0u32),
-        ("weekday", 0u32),
-        ("in the future", 0u32),
-        ("LSB is 1", 0u32),
-    ]);
-    for ulid_string in &ulids {
-        let ulid: Ulid = match Ulid::from_string(ulid_string) {
-            Ok(u) => u,
-            Err(_) => {
-                return Err(AppError::ParseULIDError);
-            }
-        };
-        let time: Timestamp = match Timestamp::from_millisecond(ulid.timestamp_ms() as i64) {
-            Ok(t) => t,
-            Err(_) => {
-                return Err(AppError::ParseTimestampError);
-            }
-        };
+    let tz = time_zone(options.tz.as_deref())?;
+    let predicates = [
+        NamedPredicate::new("christmas eve", DatePredicate::MonthDay { month: 12, day: 24 }),
+        NamedPredicate::new("weekday", DatePredicate::Weekday { weekday }),
+        NamedPredicate::new("in the future", DatePredicate::After { timestamp: clock.now() }),
+        NamedPredicate::new("LSB is 1", DatePredicate::LsbSet),
+    ];
+    validate_predicates(&predicates)?;
+    let dated = zoned_ulids(&ulids, &tz)?;
 
-        let zoned = time.to_zoned(TimeZone::UTC);
+    let temp_map: HashMap<String, u32> = analytics
+        ::evaluate(&predicates, &dated, false)
+        .into_iter()
+        .map(|result| (result.name, result.count))
+        .collect();
 
-        if zoned.month() == 12 && zoned.day() == 24 {
-            temp_map.entry("christmas eve").and_modify(|count| {
-                *count += 1;
-            });
-        }
+    Ok(serde_json::to_string(&temp_map).unwrap())
+}
 
-        if zoned.weekday().to_monday_zero_offset() == weekday {
-            temp_map.entry("weekday").and_modify(|count| {
-                *count += 1;
-            });
-        }
+#[derive(Debug, Deserialize)]
+struct AnalyzeRequest {
+    ulids: Vec<String>,
+    /// IANA timezone name, e.g. `Europe/Oslo`; defaults to UTC.
+    timezone: Option<String>,
+    predicates: Vec<NamedPredicate>,
+    #[serde(default)]
+    include_matches: bool,
+}
 
-        if time.duration_until(Timestamp::now()).as_secs() < 0 {
-            temp_map.entry("in the future").and_modify(|count| {
-                *count += 1;
-            });
-        }
+#[derive(Debug, Serialize)]
+struct AnalyzeResponse {
+    timezone: String,
+    results: Vec<PredicateResult>,
+}
 
-        if (ulid.0 & 1) == 1 {
-            temp_map.entry("LSB is 1").and_modify(|count| {
-                *count += 1;
-            });
-        }
+async fn analyze_ulids(Json(request): Json<AnalyzeRequest>) -> Result<impl IntoResponse, AppError> {
+    validate_predicates(&request.predicates)?;
+    let tz = time_zone(request.timezone.as_deref())?;
+    let dated = zoned_ulids(&request.ulids, &tz)?;
+    let results = analytics::evaluate(&request.predicates, &dated, request.include_matches);
+    Ok(
+        Json(AnalyzeResponse {
+            timezone: tz.iana_name().unwrap_or("UTC").to_string(),
+            results,
+        })
+    )
+}
+
+/// Environment variable that, when set to `1` or `true`, mounts the `/clock`
+/// admin routes and backs day12 with a [`TestClock`] they can drive.
+pub const CLOCK_ADMIN_ENV: &str = "DAY12_CLOCK_ADMIN";
+
+#[derive(Clone)]
+struct Day12State {
+    store: SharedStore,
+    clock: SharedClock,
+}
+
+impl FromRef<Day12State> for SharedStore {
+    fn from_ref(state: &Day12State) -> Self {
+        state.store.clone()
     }
+}
 
-    Ok(serde_json::to_string(&temp_map).unwrap())
+impl FromRef<Day12State> for SharedClock {
+    fn from_ref(state: &Day12State) -> Self {
+        state.clock.clone()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ClockStatus {
+    now: Timestamp,
+    frozen: bool,
 }
 
-pub fn router() -> Router {
-    let packet_map = Arc::new(RwLock::new(HashMap::<String, Timestamp>::new()));
-    Router::new()
-        .route("/save/:packet", post(save_packet))
-        .route("/load/:packet", get(load_packet))
-        .route("/ulids", post(ulid_to_uuid))
-        .route("/ulids/:weekday", post(ulid_to_dates))
-        .with_state(packet_map.clone())
+impl ClockStatus {
+    fn new(clock: &TestClock) -> Self {
+        ClockStatus { now: clock.now(), frozen: clock.is_frozen() }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FreezeRequest {
+    at: Option<Timestamp>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdvanceRequest {
+    #[serde(default)]
+    seconds: i64,
+    #[serde(default)]
+    millis: i64,
+}
+
+async fn clock_status(State(clock): State<Arc<TestClock>>) -> impl IntoResponse {
+    Json(ClockStatus::new(&clock))
+}
+
+async fn freeze_clock(
+    State(clock): State<Arc<TestClock>>,
+    request: Option<Json<FreezeRequest>>
+) -> impl IntoResponse {
+    let Json(request) = request.unwrap_or_default();
+    clock.freeze(request.at);
+    Json(ClockStatus::new(&clock))
+}
+
+async fn advance_clock(
+    State(clock): State<Arc<TestClock>>,
+    Json(request): Json<AdvanceRequest>
+) -> impl IntoResponse {
+    let by = SignedDuration::from_secs(request.seconds).saturating_add(
+        SignedDuration::from_millis(request.millis)
+    );
+    clock.advance(by);
+    Json(ClockStatus::new(&clock))
+}
+
+async fn resume_clock(State(clock): State<Arc<TestClock>>) -> impl IntoResponse {
+    clock.resume();
+    Json(ClockStatus::new(&clock))
+}
+
+async fn reset_clock(State(clock): State<Arc<TestClock>>) -> impl IntoResponse {
+    clock.reset();
+    Json(ClockStatus::new(&clock))
+}
+
+/// Routes to freeze, advance, resume and reset a [`TestClock`].
+pub fn clock_admin_router(clock: Arc<TestClock>) -> Routes {
+    Routes::new()
+        .get("/clock", clock_status)
+        .post("/clock/freeze", freeze_clock)
+        .post("/clock/advance", advance_clock)
+        .post("/clock/resume", resume_clock)
+        .post("/clock/reset", reset_clock)
+        .with_state(clock)
+}
+
+/// Builds the day12 routes over an explicit store and clock.
+pub fn router_with(store: SharedStore, clock: SharedClock) -> Routes {
+    spawn_eviction(&store, clock.clone());
+    Routes::new()
+        .post("/save/:packet", save_packet)
+        .get("/load/:packet", load_packet)
+        .post("/save", save_packets)
+        .post("/load", load_packets)
+        .get("/packets", list_packets)
+        .get("/packets/:packet", get_packet)
+        .delete("/packets/:packet", delete_packet)
+        .post("/ulids", ulid_to_uuid)
+        .post("/ulids/:weekday", ulid_to_dates)
+        .get("/ulids/generate", generate_ulids)
+        .post("/ulids/generate", generate_ulids)
+        .get("/ulids/decode/:ulid", decode_ulid)
+        .post("/ulids/sort", sort_ulids)
+        .post("/ulids/analyze", analyze_ulids)
+        .post("/uuids", uuid_to_ulid)
+        .with_state(Day12State { store, clock })
+}
+
+pub const DAY: Day = Day {
+    prefix: "/12",
+    title: "Packet timekeeping and ULIDs",
+    router,
+};
+
+pub fn router() -> Routes {
+    let store = StoreConfig::from_env()
+        .and_then(|config| config.open())
+        .expect("failed to open day12 packet store");
+    if env_flag(CLOCK_ADMIN_ENV) {
+        let clock = Arc::new(TestClock::new());
+        router_with(store, clock.clone()).merge(clock_admin_router(clock))
+    } else {
+        router_with(store, Arc::new(SystemClock))
+    }
 }
 
 // let ulid: Ulid = match Ulid::from_string(&ulid_to_save) {
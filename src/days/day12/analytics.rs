@@ -0,0 +1,140 @@
+use std::fmt;
+use jiff::{ civil::{ Date, Weekday }, Timestamp, Zoned };
+use serde::{ Deserialize, Serialize };
+use ulid::Ulid;
+
+/// A property of a ULID's embedded time, evaluated in the caller's timezone.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DatePredicate {
+    MonthDay {
+        month: i8,
+        day: i8,
+    },
+    /// Weekday as an offset from Monday (0 = Monday, 6 = Sunday).
+    Weekday {
+        weekday: i8,
+    },
+    /// Hours in `start..end`; a range with `start > end` wraps past midnight.
+    HourRange {
+        start: i8,
+        end: i8,
+    },
+    Weekend,
+    Before {
+        timestamp: Timestamp,
+    },
+    After {
+        timestamp: Timestamp,
+    },
+    LsbSet,
+}
+
+impl DatePredicate {
+    /// Rejects fields no date could match, e.g. month 13 or hour 30.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            DatePredicate::MonthDay { month, day } => {
+                // 2024 is a leap year, so February 29th is allowed.
+                Date::new(2024, *month, *day)
+                    .map(|_| ())
+                    .map_err(|_| format!("no month day {month:02}-{day:02}"))
+            }
+            DatePredicate::Weekday { weekday } if !(0..=6).contains(weekday) =>
+                Err(format!("weekday {weekday} is not in 0..=6")),
+            DatePredicate::HourRange { start, .. } if !(0..=23).contains(start) =>
+                Err(format!("start hour {start} is not in 0..=23")),
+            DatePredicate::HourRange { end, .. } if !(0..=24).contains(end) =>
+                Err(format!("end hour {end} is not in 0..=24")),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn matches(&self, ulid: &Ulid, zoned: &Zoned) -> bool {
+        match self {
+            DatePredicate::MonthDay { month, day } => zoned.month() == *month && zoned.day() == *day,
+            DatePredicate::Weekday { weekday } =>
+                zoned.weekday().to_monday_zero_offset() == *weekday,
+            DatePredicate::HourRange { start, end } => {
+                let hour = zoned.hour();
+                if start <= end {
+                    *start <= hour && hour < *end
+                } else {
+                    *start <= hour || hour < *end
+                }
+            }
+            DatePredicate::Weekend => matches!(zoned.weekday(), Weekday::Saturday | Weekday::Sunday),
+            DatePredicate::Before { timestamp } => zoned.timestamp() < *timestamp,
+            DatePredicate::After { timestamp } => zoned.timestamp() > *timestamp,
+            DatePredicate::LsbSet => (ulid.0 & 1) == 1,
+        }
+    }
+}
+
+impl fmt::Display for DatePredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatePredicate::MonthDay { month, day } => write!(f, "month day {month:02}-{day:02}"),
+            DatePredicate::Weekday { weekday } => write!(f, "weekday {weekday}"),
+            DatePredicate::HourRange { start, end } => write!(f, "hours {start}-{end}"),
+            DatePredicate::Weekend => write!(f, "weekend"),
+            DatePredicate::Before { timestamp } => write!(f, "before {timestamp}"),
+            DatePredicate::After { timestamp } => write!(f, "after {timestamp}"),
+            DatePredicate::LsbSet => write!(f, "LSB is 1"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NamedPredicate {
+    /// Key for this predicate in the response; defaults to a description of it.
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub predicate: DatePredicate,
+}
+
+impl NamedPredicate {
+    pub fn new(name: &str, predicate: DatePredicate) -> Self {
+        NamedPredicate { name: Some(name.to_string()), predicate }
+    }
+
+    /// [`DatePredicate::validate`], with the error naming this predicate.
+    pub fn validate(&self) -> Result<(), (String, String)> {
+        self.predicate.validate().map_err(|reason| (self.label(), reason))
+    }
+
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.predicate.to_string())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PredicateResult {
+    pub name: String,
+    pub count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ulids: Option<Vec<Ulid>>,
+}
+
+/// Tallies every predicate over `dated`, keeping the matching ULIDs if asked to.
+pub fn evaluate(
+    predicates: &[NamedPredicate],
+    dated: &[(Ulid, Zoned)],
+    include_matches: bool
+) -> Vec<PredicateResult> {
+    predicates
+        .iter()
+        .map(|named| {
+            let matching: Vec<Ulid> = dated
+                .iter()
+                .filter(|(ulid, zoned)| named.predicate.matches(ulid, zoned))
+                .map(|(ulid, _)| *ulid)
+                .collect();
+            PredicateResult {
+                name: named.label(),
+                count: matching.len() as u32,
+                ulids: include_matches.then_some(matching),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,91 @@
+use std::sync::{ Arc, RwLock };
+use jiff::{ SignedDuration, Timestamp };
+
+/// Source of "now" for packet timers and date analytics.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Timestamp;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::now()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct TestClockState {
+    frozen: Option<Timestamp>,
+    offset: SignedDuration,
+}
+
+/// A clock that follows system time until it is frozen or shifted.
+///
+/// While frozen, `now` only moves when `advance` is called. Unfrozen, it runs
+/// at wall-clock speed with any accumulated offset applied.
+#[derive(Debug, Default)]
+pub struct TestClock {
+    state: RwLock<TestClockState>,
+}
+
+impl TestClock {
+    pub fn new() -> Self {
+        TestClock::default()
+    }
+
+    /// Stops the clock at `at`, or at the current time if `None`.
+    pub fn freeze(&self, at: Option<Timestamp>) -> Timestamp {
+        let at = at.unwrap_or_else(|| self.now());
+        self.state.write().unwrap().frozen = Some(at);
+        at
+    }
+
+    /// Starts the clock ticking again from wherever it was frozen.
+    pub fn resume(&self) -> Timestamp {
+        let mut state = self.state.write().unwrap();
+        if let Some(frozen) = state.frozen.take() {
+            state.offset = Timestamp::now().duration_until(frozen);
+        }
+        frozen_or_offset(&state)
+    }
+
+    pub fn advance(&self, by: SignedDuration) -> Timestamp {
+        let mut state = self.state.write().unwrap();
+        match state.frozen.as_mut() {
+            Some(frozen) => {
+                *frozen = frozen.saturating_add(by);
+            }
+            None => {
+                state.offset = state.offset.saturating_add(by);
+            }
+        }
+        frozen_or_offset(&state)
+    }
+
+    /// Unfreezes the clock and drops any offset, returning to system time.
+    pub fn reset(&self) -> Timestamp {
+        *self.state.write().unwrap() = TestClockState::default();
+        Timestamp::now()
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.state.read().unwrap().frozen.is_some()
+    }
+}
+
+fn frozen_or_offset(state: &TestClockState) -> Timestamp {
+    match state.frozen {
+        Some(frozen) => frozen,
+        None => Timestamp::now().saturating_add(state.offset),
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Timestamp {
+        frozen_or_offset(&self.state.read().unwrap())
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    fs::{ self, File, OpenOptions },
+    io::{ self, BufWriter, Write },
+    path::{ Path, PathBuf },
+    sync::{ Arc, Mutex, RwLock },
+};
+use jiff::Timestamp;
+use serde::{ Deserialize, Serialize };
+use thiserror::Error;
+
+/// A running log is compacted once it has this many entries per packet.
+const COMPACT_RATIO: usize = 4;
+/// Logs shorter than this are left alone, so small ones aren't rewritten on
+/// every other write.
+pub const COMPACT_MIN_ENTRIES: usize = 1024;
+
+/// Environment variable selecting the packet store backend.
+///
+/// Unset or `memory` keeps packets in memory; `file:<path>` keeps them in an
+/// append-only log at `<path>` that is replayed and compacted on startup, and
+/// compacted again whenever it outgrows the packets it holds.
+pub const STORE_ENV: &str = "DAY12_PACKET_STORE";
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error("Packet store I/O error: {0}")] Io(#[from] std::io::Error),
+    #[error("Corrupt packet log entry: {0}")] Corrupt(#[from] serde_json::Error),
+    #[error("Unknown packet store configuration: {0}")] Config(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketRecord {
+    pub packet: String,
+    pub saved: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<Timestamp>,
+}
+
+impl PacketRecord {
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+/// Writes may block on disk I/O, so async callers should run `save`,
+/// `delete` and `evict_expired` off the runtime.
+pub trait PacketStore: Send + Sync {
+    fn save(&self, record: PacketRecord) -> Result<(), StoreError>;
+    /// Saves every record or, on error, none of them.
+    fn save_all(&self, records: Vec<PacketRecord>) -> Result<(), StoreError>;
+    fn load(&self, packet: &str) -> Result<Option<PacketRecord>, StoreError>;
+    /// Returns whether a packet still live at `now` existed; an expired one is
+    /// removed all the same but reported as missing.
+    fn delete(&self, packet: &str, now: Timestamp) -> Result<bool, StoreError>;
+    /// Returns one page of the records still live at `now`, ordered by packet
+    /// name, plus how many live records there are in all.
+    fn list(
+        &self,
+        now: Timestamp,
+        offset: usize,
+        limit: usize
+    ) -> Result<(Vec<PacketRecord>, usize), StoreError>;
+    /// Removes every record whose TTL has passed and returns how many were dropped.
+    fn evict_expired(&self, now: Timestamp) -> Result<usize, StoreError>;
+}
+
+pub type SharedStore = Arc<dyn PacketStore>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreConfig {
+    Memory,
+    File(PathBuf),
+}
+
+impl StoreConfig {
+    pub fn from_env() -> Result<Self, StoreError> {
+        match env::var(STORE_ENV) {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(StoreConfig::Memory),
+        }
+    }
+
+    pub fn open(&self) -> Result<SharedStore, StoreError> {
+        match self {
+            StoreConfig::Memory => Ok(Arc::new(MemoryStore::default())),
+            StoreConfig::File(path) => Ok(Arc::new(FileStore::open(path)?)),
+        }
+    }
+}
+
+impl std::str::FromStr for StoreConfig {
+    type Err = StoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" | "memory" => Ok(StoreConfig::Memory),
+            other =>
+                match other.strip_prefix("file:") {
+                    Some(path) if !path.is_empty() => Ok(StoreConfig::File(PathBuf::from(path))),
+                    _ => Err(StoreError::Config(other.to_string())),
+                }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    packets: RwLock<BTreeMap<String, PacketRecord>>,
+}
+
+impl MemoryStore {
+    fn expired(&self, now: Timestamp) -> Vec<String> {
+        self.packets
+            .read()
+            .unwrap()
+            .values()
+            .filter(|record| record.is_expired(now))
+            .map(|record| record.packet.clone())
+            .collect()
+    }
+}
+
+impl PacketStore for MemoryStore {
+    fn save(&self, record: PacketRecord) -> Result<(), StoreError> {
+        self.packets.write().unwrap().insert(record.packet.clone(), record);
+        Ok(())
+    }
+
+    fn save_all(&self, records: Vec<PacketRecord>) -> Result<(), StoreError> {
+        let mut packets = self.packets.write().unwrap();
+        for record in records {
+            packets.insert(record.packet.clone(), record);
+        }
+        Ok(())
+    }
+
+    fn load(&self, packet: &str) -> Result<Option<PacketRecord>, StoreError> {
+        Ok(self.packets.read().unwrap().get(packet).cloned())
+    }
+
+    fn delete(&self, packet: &str, now: Timestamp) -> Result<bool, StoreError> {
+        let removed = self.packets.write().unwrap().remove(packet);
+        Ok(removed.is_some_and(|record| !record.is_expired(now)))
+    }
+
+    fn list(
+        &self,
+        now: Timestamp,
+        offset: usize,
+        limit: usize
+    ) -> Result<(Vec<PacketRecord>, usize), StoreError> {
+        let packets = self.packets.read().unwrap();
+        let live = || packets.values().filter(|record| !record.is_expired(now));
+        let page = live().skip(offset).take(limit).cloned().collect();
+        Ok((page, live().count()))
+    }
+
+    fn evict_expired(&self, now: Timestamp) -> Result<usize, StoreError> {
+        let mut packets = self.packets.write().unwrap();
+        let before = packets.len();
+        packets.retain(|_, record| !record.is_expired(now));
+        Ok(before - packets.len())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum LogEntry {
+    Save(PacketRecord),
+    Delete {
+        packet: String,
+    },
+}
+
+impl MemoryStore {
+    fn apply(&self, entry: LogEntry) {
+        let mut packets = self.packets.write().unwrap();
+        match entry {
+            LogEntry::Save(record) => {
+                packets.insert(record.packet.clone(), record);
+            }
+            LogEntry::Delete { packet } => {
+                packets.remove(&packet);
+            }
+        }
+    }
+}
+
+/// Append-only JSON-lines log of packet operations.
+///
+/// The whole log is replayed into memory on open, so reads never touch disk,
+/// and then rewritten with one `save` per packet so it doesn't grow forever;
+/// it is rewritten the same way whenever it gets [`COMPACT_RATIO`] times
+/// longer than that. Every write holds the log lock from its check through to
+/// the in-memory update, so writers never act on a stale view of the packets.
+pub struct FileStore {
+    packets: MemoryStore,
+    log: Mutex<Log>,
+}
+
+struct Log {
+    path: PathBuf,
+    file: File,
+    /// Bytes written, so a failed append can be cut off again.
+    len: u64,
+    entries: usize,
+}
+
+impl Log {
+    /// Compacts the log at `path` down to `packets` and opens it for appending.
+    fn open(path: &Path, packets: &MemoryStore) -> Result<Self, StoreError> {
+        let entries = compact(path, packets)?;
+        let file = OpenOptions::new().append(true).open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Log { path: path.to_path_buf(), file, len, entries })
+    }
+
+    fn append(&mut self, lines: &[u8]) -> io::Result<()> {
+        let written = self.file.write_all(lines).and_then(|()| self.file.sync_data());
+        if let Err(e) = written {
+            // Don't leave a torn line for the next append to land after.
+            self.file.set_len(self.len)?;
+            return Err(e);
+        }
+        self.len += lines.len() as u64;
+        Ok(())
+    }
+}
+
+impl FileStore {
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        let packets = MemoryStore::default();
+        if path.exists() {
+            replay(path, &packets)?;
+        }
+        let log = Log::open(path, &packets)?;
+        Ok(FileStore { packets, log: Mutex::new(log) })
+    }
+
+    /// Appends `entries` to the log and applies them in memory without
+    /// releasing the log lock, so memory always matches the log's order.
+    fn commit(&self, log: &mut Log, entries: Vec<LogEntry>) -> Result<(), StoreError> {
+        let mut lines = String::new();
+        for entry in &entries {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        log.append(lines.as_bytes())?;
+        log.entries += entries.len();
+        for entry in entries {
+            self.packets.apply(entry);
+        }
+
+        let live = self.packets.packets.read().unwrap().len();
+        if log.entries >= COMPACT_MIN_ENTRIES && log.entries > COMPACT_RATIO * live {
+            // The entries are committed either way; a failed compaction just
+            // leaves the longer log in place to be tried again.
+            match Log::open(&log.path, &self.packets) {
+                Ok(compacted) => {
+                    *log = compacted;
+                }
+                Err(e) => tracing::error!(error = %e, "day12 packet log compaction failed"),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replays the log at `path` into `packets`.
+///
+/// A crash or a full disk can tear the last append, so an unterminated or
+/// unreadable final line is dropped with a warning (compaction then cuts it
+/// off); a bad line anywhere else means the log really is corrupt.
+fn replay(path: &Path, packets: &MemoryStore) -> Result<(), StoreError> {
+    let bytes = fs::read(path)?;
+    let lines: Vec<&[u8]> = bytes.split(|&byte| byte == b'\n').collect();
+    // Whatever follows the last newline: empty unless the last append was torn.
+    let (unterminated, complete) = lines.split_last().expect("split yields at least one piece");
+    for (index, line) in complete.iter().enumerate() {
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+        match serde_json::from_slice(line) {
+            Ok(entry) => packets.apply(entry),
+            Err(e) if index + 1 == complete.len() && unterminated.is_empty() => {
+                tracing::warn!(
+                    path = %path.display(),
+                    error = %e,
+                    "dropping unreadable last day12 packet log line"
+                );
+            }
+            Err(e) => {
+                return Err(e.into());
+            }
+        }
+    }
+    if !unterminated.trim_ascii().is_empty() {
+        tracing::warn!(path = %path.display(), "dropping unterminated last day12 packet log line");
+    }
+    Ok(())
+}
+
+/// Replaces the log at `path` with one `save` per packet, via a temporary
+/// file so a crash part way through leaves the old log in place. Returns how
+/// many entries the new log has.
+fn compact(path: &Path, packets: &MemoryStore) -> Result<usize, StoreError> {
+    let mut temp = OsString::from(path);
+    temp.push(".compacting");
+    let mut writer = BufWriter::new(File::create(&temp)?);
+    let packets = packets.packets.read().unwrap();
+    for record in packets.values() {
+        serde_json::to_writer(&mut writer, &LogEntry::Save(record.clone()))?;
+        writer.write_all(b"\n")?;
+    }
+    writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&temp, path)?;
+    Ok(packets.len())
+}
+
+impl PacketStore for FileStore {
+    fn save(&self, record: PacketRecord) -> Result<(), StoreError> {
+        let mut log = self.log.lock().unwrap();
+        self.commit(&mut log, vec![LogEntry::Save(record)])
+    }
+
+    fn save_all(&self, records: Vec<PacketRecord>) -> Result<(), StoreError> {
+        let mut log = self.log.lock().unwrap();
+        self.commit(&mut log, records.into_iter().map(LogEntry::Save).collect())
+    }
+
+    fn load(&self, packet: &str) -> Result<Option<PacketRecord>, StoreError> {
+        self.packets.load(packet)
+    }
+
+    fn delete(&self, packet: &str, now: Timestamp) -> Result<bool, StoreError> {
+        let mut log = self.log.lock().unwrap();
+        let Some(record) = self.packets.load(packet)? else {
+            return Ok(false);
+        };
+        self.commit(&mut log, vec![LogEntry::Delete { packet: packet.to_string() }])?;
+        Ok(!record.is_expired(now))
+    }
+
+    fn list(
+        &self,
+        now: Timestamp,
+        offset: usize,
+        limit: usize
+    ) -> Result<(Vec<PacketRecord>, usize), StoreError> {
+        self.packets.list(now, offset, limit)
+    }
+
+    fn evict_expired(&self, now: Timestamp) -> Result<usize, StoreError> {
+        let mut log = self.log.lock().unwrap();
+        let expired = self.packets.expired(now);
+        if expired.is_empty() {
+            return Ok(0);
+        }
+        let count = expired.len();
+        let entries = expired
+            .into_iter()
+            .map(|packet| LogEntry::Delete { packet })
+            .collect();
+        self.commit(&mut log, entries)?;
+        Ok(count)
+    }
+}
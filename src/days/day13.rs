@@ -0,0 +1,183 @@
+use std::{env, str::FromStr};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    Executor, FromRow, SqlitePool,
+};
+use thiserror::Error;
+
+use crate::error::Problem;
+use crate::extract::Json;
+use crate::registry::{Day, Routes};
+
+/// Environment variable overriding the SQLite database URL.
+pub const DATABASE_URL_ENV: &str = "DAY13_DATABASE_URL";
+const DEFAULT_DATABASE_URL: &str = "sqlite::memory:";
+
+pub(crate) const CREATE_ORDERS: &str = "CREATE TABLE IF NOT EXISTS orders (
+    id INT PRIMARY KEY,
+    region_id INT,
+    gift_name VARCHAR(50),
+    quantity INT
+)";
+
+/// Driver messages stay in the logs; clients only see the error's kind.
+#[derive(Error, Debug)]
+enum AppError {
+    #[error("An order with that id already exists")]
+    DuplicateOrder,
+    #[error("Database error")]
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        if is_duplicate(&e) {
+            AppError::DuplicateOrder
+        } else {
+            AppError::Database(e)
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, code) = match &self {
+            AppError::DuplicateOrder => (StatusCode::CONFLICT, "duplicate_order"),
+            AppError::Database(e) => {
+                tracing::error!(error = %e, "day13 database error");
+                (StatusCode::INTERNAL_SERVER_ERROR, "database")
+            }
+        };
+
+        Problem::new(status, code, self.to_string()).into_response()
+    }
+}
+
+/// Whether an insert failed because a row with the same key exists.
+pub(crate) fn is_duplicate(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Database(db) if db.is_unique_violation())
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub(crate) struct Order {
+    id: i64,
+    region_id: i64,
+    gift_name: String,
+    quantity: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct Total {
+    total: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct Popular {
+    popular: Option<String>,
+}
+
+/// Opens the database lazily; `schema` runs on connect, so it should only
+/// create what doesn't exist yet.
+///
+/// The pool holds a single connection that never expires, because every
+/// connection to `sqlite::memory:` gets its own empty database.
+pub fn connect(url: &str, schema: &'static [&'static str]) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+    Ok(SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .after_connect(move |conn, _| {
+            Box::pin(async move {
+                for statement in schema {
+                    conn.execute(*statement).await?;
+                }
+                Ok(())
+            })
+        })
+        .connect_lazy_with(options))
+}
+
+async fn sql(State(pool): State<SqlitePool>) -> Result<String, AppError> {
+    let (value,): (i64,) = sqlx::query_as("SELECT 20231213").fetch_one(&pool).await?;
+    Ok(value.to_string())
+}
+
+async fn reset(State(pool): State<SqlitePool>) -> Result<StatusCode, AppError> {
+    let mut tx = pool.begin().await?;
+    tx.execute("DROP TABLE IF EXISTS orders").await?;
+    tx.execute(CREATE_ORDERS).await?;
+    tx.commit().await?;
+    Ok(StatusCode::OK)
+}
+
+/// Inserts every order in one transaction.
+pub(crate) async fn insert(pool: &SqlitePool, orders: Vec<Order>) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for order in orders {
+        sqlx::query("INSERT INTO orders (id, region_id, gift_name, quantity) VALUES (?, ?, ?, ?)")
+            .bind(order.id)
+            .bind(order.region_id)
+            .bind(order.gift_name)
+            .bind(order.quantity)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await
+}
+
+async fn insert_orders(
+    State(pool): State<SqlitePool>,
+    Json(orders): Json<Vec<Order>>,
+) -> Result<StatusCode, AppError> {
+    insert(&pool, orders).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn total(State(pool): State<SqlitePool>) -> Result<Json<Total>, AppError> {
+    let (total,): (Option<i64>,) = sqlx::query_as("SELECT SUM(quantity) FROM orders")
+        .fetch_one(&pool)
+        .await?;
+    Ok(Json(Total {
+        total: total.unwrap_or(0),
+    }))
+}
+
+async fn popular(State(pool): State<SqlitePool>) -> Result<Json<Popular>, AppError> {
+    let popular: Option<(String,)> = sqlx::query_as(
+        "SELECT gift_name FROM orders GROUP BY gift_name ORDER BY SUM(quantity) DESC LIMIT 1",
+    )
+    .fetch_optional(&pool)
+    .await?;
+    Ok(Json(Popular {
+        popular: popular.map(|(gift_name,)| gift_name),
+    }))
+}
+
+pub const DAY: Day = Day {
+    prefix: "/13",
+    title: "Gift orders",
+    router,
+};
+
+pub fn router_with(pool: SqlitePool) -> Routes {
+    Routes::new()
+        .get("/sql", sql)
+        .post("/reset", reset)
+        .post("/orders", insert_orders)
+        .get("/orders/total", total)
+        .get("/orders/popular", popular)
+        .with_state(pool)
+}
+
+pub fn router() -> Routes {
+    let url = env::var(DATABASE_URL_ENV).unwrap_or(DEFAULT_DATABASE_URL.to_string());
+    router_with(connect(&url, &[CREATE_ORDERS]).expect("invalid day13 database URL"))
+}
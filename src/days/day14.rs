@@ -1,41 +1,160 @@
-use axum::{response::IntoResponse, routing::post, Json, Router};
-use serde::{Deserialize, Serialize};
+pub mod escape;
+pub mod markdown;
+pub mod templates;
+
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    middleware,
+    http::StatusCode,
+    response::{ Html, IntoResponse, Response },
+    Extension,
+};
+use minijinja::{ context, Value };
+use serde::{ Deserialize, Serialize };
+use thiserror::Error;
+
+use crate::config::env_flag;
+use crate::error::Problem;
+use crate::extract::{ Json, Path, Query };
+use crate::registry::{ Day, Routes };
+use crate::security::{ security_headers, CspNonce, SecurityHeaders };
+use templates::{ TemplateEngine, TemplateError, PAGE_TEMPLATE };
+
+#[derive(Error, Debug)]
+enum AppError {
+    #[error(transparent)] Template(#[from] TemplateError),
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, code) = match self {
+            AppError::Template(TemplateError::NotFound(_)) =>
+                (StatusCode::NOT_FOUND, "template_not_found"),
+            AppError::Template(TemplateError::InvalidName(_)) =>
+                (StatusCode::BAD_REQUEST, "invalid_template_name"),
+            AppError::Template(TemplateError::OnDisk(_)) =>
+                (StatusCode::CONFLICT, "template_on_disk"),
+            AppError::Template(TemplateError::TooLarge) =>
+                (StatusCode::PAYLOAD_TOO_LARGE, "template_too_large"),
+            AppError::Template(TemplateError::TooMany) =>
+                (StatusCode::INSUFFICIENT_STORAGE, "too_many_templates"),
+            AppError::Template(TemplateError::Syntax(_)) =>
+                (StatusCode::BAD_REQUEST, "template_syntax"),
+            AppError::Template(TemplateError::Render(_)) =>
+                (StatusCode::UNPROCESSABLE_ENTITY, "template_render"),
+        };
+
+        Problem::new(status, code, self.to_string()).into_response()
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 struct HtmlContent {
     content: String,
 }
 
-async fn unsafe_html(Json(insert_html): Json<HtmlContent>) -> impl IntoResponse {
-    format!(
-        r#"<html>
-  <head>
-    <title>CCH23 Day 14</title>
-  </head>
-  <body>
-    {}
-  </body>
-</html>"#,
-        insert_html.content
-    )
-}
-
-async fn safe_html(Json(insert_html): Json<HtmlContent>) -> impl IntoResponse {
-    format!(
-        r#"<html>
-  <head>
-    <title>CCH23 Day 14</title>
-  </head>
-  <body>
-    {}
-  </body>
-</html>"#,
-        html_escape::encode_double_quoted_attribute(&insert_html.content)
-    )
-}
-
-pub fn router() -> Router {
-    Router::new()
-        .route("/unsafe", post(unsafe_html))
-        .route("/safe", post(safe_html))
+type SharedEngine = Arc<TemplateEngine>;
+
+async fn unsafe_html(
+    State(engine): State<SharedEngine>,
+    Extension(CspNonce(csp_nonce)): Extension<CspNonce>,
+    Json(insert_html): Json<HtmlContent>
+) -> Result<impl IntoResponse, AppError> {
+    let content = Value::from_safe_string(insert_html.content);
+    Ok(Html(engine.render(PAGE_TEMPLATE, context! { content, csp_nonce })?))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SafeMode {
+    /// Show the content as literal text.
+    #[default]
+    Escape,
+    /// Keep allowlisted markup such as `<b>` and `<a href>`, drop the rest.
+    Sanitize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SafeOptions {
+    #[serde(default)]
+    mode: SafeMode,
+}
+
+async fn safe_html(
+    Query(options): Query<SafeOptions>,
+    State(engine): State<SharedEngine>,
+    Extension(CspNonce(csp_nonce)): Extension<CspNonce>,
+    Json(insert_html): Json<HtmlContent>
+) -> Result<impl IntoResponse, AppError> {
+    let content = match options.mode {
+        SafeMode::Escape => Value::from(insert_html.content),
+        SafeMode::Sanitize => Value::from_safe_string(escape::sanitize(&insert_html.content)),
+    };
+    Ok(Html(engine.render(PAGE_TEMPLATE, context! { content, csp_nonce })?))
+}
+
+async fn markdown_html(
+    State(engine): State<SharedEngine>,
+    Extension(CspNonce(csp_nonce)): Extension<CspNonce>,
+    markdown: String
+) -> Result<impl IntoResponse, AppError> {
+    let content = Value::from_safe_string(markdown::to_html(&markdown));
+    Ok(Html(engine.render(PAGE_TEMPLATE, context! { content, csp_nonce })?))
+}
+
+async fn render_template(
+    Path(template): Path<String>,
+    State(engine): State<SharedEngine>,
+    Extension(CspNonce(csp_nonce)): Extension<CspNonce>,
+    Json(context): Json<serde_json::Value>
+) -> Result<impl IntoResponse, AppError> {
+    let context = context! { csp_nonce, ..Value::from_serialize(&context) };
+    Ok(Html(engine.render(&template, context)?))
+}
+
+/// Environment variable that, when set to `1` or `true`, mounts
+/// `PUT /templates/:template` so templates can be uploaded at runtime.
+pub const TEMPLATE_UPLOADS_ENV: &str = "DAY14_TEMPLATE_UPLOADS";
+
+async fn list_templates(State(engine): State<SharedEngine>) -> impl IntoResponse {
+    Json(engine.names())
+}
+
+async fn upload_template(
+    Path(template): Path<String>,
+    State(engine): State<SharedEngine>,
+    source: String
+) -> Result<impl IntoResponse, AppError> {
+    engine.add(&template, source)?;
+    Ok(StatusCode::CREATED)
+}
+
+pub const DAY: Day = Day {
+    prefix: "/14",
+    title: "HTML rendering",
+    router,
+};
+
+/// Builds the day14 routes over an explicit engine and header policy.
+pub fn router_with(engine: TemplateEngine, headers: SecurityHeaders, uploads: bool) -> Routes {
+    let mut router = Routes::new()
+        .post("/unsafe", unsafe_html)
+        .post("/safe", safe_html)
+        .post("/markdown", markdown_html)
+        .post("/render/:template", render_template)
+        .get("/templates", list_templates);
+    if uploads {
+        router = router.put("/templates/:template", upload_template);
+    }
+    router
+        .with_state(Arc::new(engine))
+        .layer(middleware::from_fn_with_state(Arc::new(headers), security_headers))
+}
+
+pub fn router() -> Routes {
+    let uploads = env_flag(TEMPLATE_UPLOADS_ENV);
+    let headers = SecurityHeaders::from_env().expect("invalid day14 security headers");
+    router_with(TemplateEngine::from_env(), headers, uploads)
 }
@@ -0,0 +1,63 @@
+use std::{ borrow::Cow, fmt::Write };
+
+/// Escapes text that sits between tags; quotes are left alone.
+pub fn text(input: &str) -> Cow<'_, str> {
+    html_escape::encode_text(input)
+}
+
+/// Escapes a value for a quoted attribute, single or double.
+pub fn attribute(input: &str) -> Cow<'_, str> {
+    html_escape::encode_quoted_attribute(input)
+}
+
+const SAFE_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// Makes a URL safe for an `href` or `src` attribute.
+///
+/// Absolute URLs must use an allowlisted scheme, otherwise the result is `#`.
+/// Anything outside printable ASCII is percent-encoded before the attribute
+/// escaping is applied.
+pub fn url(input: &str) -> String {
+    let trimmed = input.trim();
+    if let Some((scheme, _)) = trimmed.split_once(':') {
+        let looks_like_scheme = !scheme.contains(['/', '?', '#']);
+        if looks_like_scheme && !SAFE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) {
+            return "#".to_string();
+        }
+    }
+    let mut encoded = String::with_capacity(trimmed.len());
+    for byte in trimmed.bytes() {
+        if byte.is_ascii_graphic() && !matches!(byte, b'"' | b'\'' | b'<' | b'>' | b'`') {
+            encoded.push(byte as char);
+        } else {
+            write!(encoded, "%{byte:02X}").unwrap();
+        }
+    }
+    attribute(&encoded).into_owned()
+}
+
+/// Encodes JSON for use inside a `<script>` block.
+///
+/// The result is a valid JavaScript literal that can't close the script tag,
+/// open an HTML comment, or break on the JavaScript line separators.
+pub fn script(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        match c {
+            '<' => escaped.push_str("\\u003c"),
+            '>' => escaped.push_str("\\u003e"),
+            '&' => escaped.push_str("\\u0026"),
+            '\'' => escaped.push_str("\\u0027"),
+            '\u{2028}' => escaped.push_str("\\u2028"),
+            '\u{2029}' => escaped.push_str("\\u2029"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Keeps an allowlist of formatting tags and safe attributes, dropping
+/// scripts, styles, event handlers and dangerous URL schemes.
+pub fn sanitize(input: &str) -> String {
+    ammonia::clean(input)
+}
@@ -0,0 +1,13 @@
+use pulldown_cmark::{ html, Options, Parser };
+
+/// Renders CommonMark with tables, fenced code and strikethrough, then runs
+/// the result through the allowlist sanitizer so embedded raw HTML can't
+/// smuggle in scripts or event handlers.
+pub fn to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let mut rendered = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut rendered, Parser::new_ext(markdown, options));
+
+    // Fenced code blocks carry their language as `class="language-…"`.
+    ammonia::Builder::default().add_tag_attributes("code", &["class"]).clean(&rendered).to_string()
+}
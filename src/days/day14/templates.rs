@@ -0,0 +1,153 @@
+use std::{ collections::BTreeSet, env, fs, path::PathBuf, sync::RwLock };
+use minijinja::{ path_loader, AutoEscape, Environment, ErrorKind, Output, State, Value };
+use thiserror::Error;
+
+use super::escape;
+
+/// Environment variable pointing at the directory named templates are read from.
+pub const TEMPLATE_DIR_ENV: &str = "DAY14_TEMPLATE_DIR";
+const DEFAULT_TEMPLATE_DIR: &str = "templates/day14";
+
+/// Most templates that can be uploaded at runtime.
+pub const MAX_UPLOADED_TEMPLATES: usize = 64;
+/// Largest template source, in bytes, that can be uploaded.
+pub const MAX_TEMPLATE_SIZE: usize = 64 * 1024;
+
+/// The page shell every day14 HTML route renders into.
+pub const PAGE_TEMPLATE: &str = "page.html";
+const PAGE_SOURCE: &str = include_str!("../../../templates/day14/page.html");
+
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[error("Template {0} not found")] NotFound(String),
+    #[error("Invalid template name {0}")] InvalidName(String),
+    #[error("Template {0} is on disk and can't be replaced")] OnDisk(String),
+    #[error("Templates are limited to {MAX_TEMPLATE_SIZE} bytes")] TooLarge,
+    #[error("At most {MAX_UPLOADED_TEMPLATES} templates can be uploaded")] TooMany,
+    #[error("Template syntax error: {0}")] Syntax(minijinja::Error),
+    #[error("Template render error: {0}")] Render(minijinja::Error),
+}
+
+/// Named templates loaded from disk or uploaded at runtime.
+///
+/// Every template is HTML-escaped on output unless a value is marked safe.
+pub struct TemplateEngine {
+    dir: PathBuf,
+    templates: RwLock<Templates>,
+}
+
+struct Templates {
+    env: Environment<'static>,
+    /// Names added through [`TemplateEngine::add`].
+    uploaded: BTreeSet<String>,
+}
+
+impl TemplateEngine {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let mut env = Environment::new();
+        env.set_loader(path_loader(&dir));
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_auto_escape_callback(|_| AutoEscape::Html);
+        env.set_formatter(escape);
+        env.add_filter("text", |value: Value| escape_with(value, |s| escape::text(s).into_owned()));
+        env.add_filter("attr", |value: Value| escape_with(value, |s| escape::attribute(s).into_owned()));
+        env.add_filter("url", |value: Value| escape_with(value, escape::url));
+        env.add_filter("sanitize", |value: Value| escape_with(value, escape::sanitize));
+        env.add_filter("js", script_filter);
+        env.add_template(PAGE_TEMPLATE, PAGE_SOURCE).expect("page shell template is valid");
+        let templates = RwLock::new(Templates { env, uploaded: BTreeSet::new() });
+        TemplateEngine { dir, templates }
+    }
+
+    pub fn from_env() -> Self {
+        TemplateEngine::new(env::var(TEMPLATE_DIR_ENV).unwrap_or(DEFAULT_TEMPLATE_DIR.to_string()))
+    }
+
+    /// Compiles and registers `source` under `name`, replacing an earlier
+    /// upload of the same name but never a template on disk.
+    pub fn add(&self, name: &str, source: String) -> Result<(), TemplateError> {
+        if !is_valid_name(name) || name == PAGE_TEMPLATE {
+            return Err(TemplateError::InvalidName(name.to_string()));
+        }
+        if source.len() > MAX_TEMPLATE_SIZE {
+            return Err(TemplateError::TooLarge);
+        }
+        if self.dir.join(name).exists() {
+            return Err(TemplateError::OnDisk(name.to_string()));
+        }
+        let mut templates = self.templates.write().unwrap();
+        if !templates.uploaded.contains(name) && templates.uploaded.len() >= MAX_UPLOADED_TEMPLATES {
+            return Err(TemplateError::TooMany);
+        }
+        templates.env.add_template_owned(name.to_string(), source).map_err(TemplateError::Syntax)?;
+        templates.uploaded.insert(name.to_string());
+        Ok(())
+    }
+
+    pub fn render(&self, name: &str, context: Value) -> Result<String, TemplateError> {
+        let templates = self.templates.read().unwrap();
+        let template = templates.env
+            .get_template(name).map_err(|e| {
+            match e.kind() {
+                ErrorKind::TemplateNotFound => TemplateError::NotFound(name.to_string()),
+                _ => TemplateError::Syntax(e),
+            }
+        })?;
+        template.render(context).map_err(TemplateError::Render)
+    }
+
+    /// Names of every template that can be rendered, on disk or uploaded.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: BTreeSet<String> = self.templates
+            .read()
+            .unwrap()
+            .env
+            .templates()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        if let Ok(entries) = fs::read_dir(&self.dir) {
+            names.extend(
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter_map(|entry| entry.file_name().into_string().ok())
+                    .filter(|name| is_valid_name(name))
+            );
+        }
+        names.into_iter().collect()
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    match name.strip_suffix(".html") {
+        Some(stem) =>
+            !stem.is_empty() &&
+                stem.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+        None => false,
+    }
+}
+
+/// Applies a context escaper unless the value has already been marked safe.
+fn escape_with(value: Value, escaper: impl Fn(&str) -> String) -> Value {
+    if value.is_safe() {
+        return value;
+    }
+    Value::from_safe_string(escaper(&value.to_string()))
+}
+
+fn script_filter(value: Value) -> Result<Value, minijinja::Error> {
+    let json = serde_json::to_string(&value).map_err(|e| {
+        minijinja::Error::new(ErrorKind::BadSerialization, "value can't be encoded for a script").with_source(e)
+    })?;
+    Ok(Value::from_safe_string(escape::script(&json)))
+}
+
+/// Default output escaping: quote-safe, so it holds in text and quoted attributes alike.
+fn escape(out: &mut Output, state: &State, value: &Value) -> Result<(), minijinja::Error> {
+    if state.auto_escape() == AutoEscape::None || value.is_safe() || value.is_none() {
+        return minijinja::escape_formatter(out, state, value);
+    }
+    let text = value.to_string();
+    out.write_str(&escape::attribute(&text)).map_err(minijinja::Error::from)
+}
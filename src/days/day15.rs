@@ -1,28 +1,67 @@
-use std::collections::{HashMap, HashSet};
+pub mod breach;
+pub mod policy;
+
+use std::{collections::BTreeMap, sync::Arc};
 
 use axum::{
+    async_trait,
+    extract::{FromRef, FromRequest, Request, State},
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::post,
-    Router,
 };
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use thiserror::Error;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::error::Problem;
+use crate::extract::{Json, Path, Query};
+use crate::registry::{Day, Routes};
+use breach::{is_prefix, BreachCorpus};
+use policy::{is_letter, Failure, Policy, PolicySet, Strength, GAME_POLICY};
+
+type SharedPolicies = Arc<PolicySet>;
+type SharedCorpus = Option<Arc<BreachCorpus>>;
+
+#[derive(Clone)]
+struct Day15State {
+    policies: SharedPolicies,
+    corpus: SharedCorpus,
+}
+
+impl FromRef<Day15State> for SharedPolicies {
+    fn from_ref(state: &Day15State) -> Self {
+        state.policies.clone()
+    }
+}
+
+impl FromRef<Day15State> for SharedCorpus {
+    fn from_ref(state: &Day15State) -> Self {
+        state.corpus.clone()
+    }
+}
 
 #[derive(Error, Debug)]
 enum AppError {
-    #[error("Parse error")]
-    ParseError(serde_json::Error),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("Policy {0} not found")]
+    PolicyNotFound(String),
+    #[error("Invalid hash prefix {0}: expected 5 hex digits")]
+    InvalidPrefix(String),
+    #[error("No breach corpus is loaded")]
+    CorpusUnavailable,
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::ParseError(e) => (StatusCode::BAD_REQUEST, format!("Parse error: {e}")),
+        let (status, code) = match self {
+            AppError::InvalidInput(_) => (StatusCode::BAD_REQUEST, "invalid_input"),
+            AppError::PolicyNotFound(_) => (StatusCode::NOT_FOUND, "policy_not_found"),
+            AppError::InvalidPrefix(_) => (StatusCode::BAD_REQUEST, "invalid_prefix"),
+            AppError::CorpusUnavailable => (StatusCode::SERVICE_UNAVAILABLE, "corpus_unavailable"),
         };
 
-        (status, error_message).into_response()
+        Problem::new(status, code, self.to_string()).into_response()
     }
 }
 
@@ -32,148 +71,252 @@ struct NiceInput {
 }
 
 impl NiceInput {
-    fn is_nice(&self) -> String {
-        let doubled = self.input.chars().any(|c| {
-            let doubled_char = format!("{}{}", c, c);
-            self.input.find(&doubled_char).is_some() && c.is_alphabetic()
-        });
-        if !doubled {
-            return "naughty".to_string();
-        }
+    /// Each nice/naughty check in order, with its reason code and outcome.
+    fn checks(&self) -> [(&'static str, &'static str, bool); 3] {
+        let graphemes: Vec<&str> = self.input.graphemes(true).collect();
+        let doubled = graphemes
+            .windows(2)
+            .any(|pair| pair[0] == pair[1] && is_letter(pair[0]));
 
         let substrings = ["ab", "cd", "pq", "xy"];
-        for ss in substrings {
-            if self.input.find(ss).is_some() {
-                return "naughty".to_string();
-            }
-        }
+        let forbidden = substrings.iter().any(|ss| self.input.contains(ss));
 
-        let vowels: HashSet<char> = "aeiouy".chars().collect();
-        let num_vowels = self.input.chars().filter(|c| vowels.contains(c)).count();
-        if num_vowels < 3 {
-            return "naughty".to_string();
-        }
+        let num_vowels = graphemes
+            .iter()
+            .filter(|g| matches!(**g, "a" | "e" | "i" | "o" | "u" | "y"))
+            .count();
 
-        return "nice".to_string();
+        [
+            ("doubled_letter", "no doubled letter", doubled),
+            (
+                "forbidden_substring",
+                "contains ab, cd, pq or xy",
+                !forbidden,
+            ),
+            ("vowels", "fewer than 3 vowels", num_vowels >= 3),
+        ]
+    }
+
+    fn failures(&self) -> Vec<Failure> {
+        self.checks()
+            .into_iter()
+            .filter(|(_, _, passed)| !passed)
+            .map(|(rule, reason, _)| Failure {
+                rule: rule.to_string(),
+                status: StatusCode::BAD_REQUEST.as_u16(),
+                reason: reason.to_string(),
+            })
+            .collect()
+    }
+
+    fn is_nice(&self) -> bool {
+        self.checks().iter().all(|(_, _, passed)| *passed)
     }
 }
 
-async fn nice(body: String) -> Result<impl IntoResponse, AppError> {
-    let input: NiceInput = match serde_json::from_str(&body) {
-        Ok(input) => input,
-        Err(e) => {
-            return Err(AppError::ParseError(e));
-        }
-    };
+/// A JSON `{"input": ...}` body; accepted with or without a JSON content type.
+struct Input(NiceInput);
 
-    let temp_map = HashMap::from([("result".to_string(), input.is_nice())]);
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for Input {
+    type Rejection = AppError;
 
-    match input.is_nice().as_str() {
-        "nice" => Ok((StatusCode::OK, serde_json::to_string(&temp_map).unwrap())),
-        "naughty" => Ok((
-            StatusCode::BAD_REQUEST,
-            serde_json::to_string(&temp_map).unwrap(),
-        )),
-        _ => unreachable!(),
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let body = String::from_request(request, state)
+            .await
+            .map_err(|e| AppError::InvalidInput(e.body_text()))?;
+        serde_json::from_str(&body)
+            .map(Input)
+            .map_err(|e| AppError::InvalidInput(e.to_string()))
     }
 }
 
-async fn game(body: String) -> Result<impl IntoResponse, AppError> {
-    let input: NiceInput = match serde_json::from_str(&body) {
-        Ok(input) => input,
-        Err(e) => {
-            return Err(AppError::ParseError(e));
-        }
-    };
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Nice,
+    Naughty,
+}
 
-    if input.input.len() < 8 {
-        let ret_string = format!(r#"{{"result":"naughty","reason":"8 chars"}}"#);
-        return Ok((StatusCode::BAD_REQUEST, ret_string));
+impl Outcome {
+    fn from_nice(nice: bool) -> Self {
+        if nice {
+            Outcome::Nice
+        } else {
+            Outcome::Naughty
+        }
     }
+}
 
-    if !input.input.chars().any(|c| c.is_uppercase())
-        || !input.input.chars().any(|c| c.is_lowercase())
-        || !input.input.chars().any(|c| c.is_digit(10))
-    {
-        let ret_string = format!(r#"{{"result":"naughty","reason":"more types of chars"}}"#);
-        return Ok((StatusCode::BAD_REQUEST, ret_string));
-    }
+#[derive(Debug, Serialize)]
+struct Verdict {
+    result: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
 
-    if input.input.chars().filter(|c| c.is_digit(10)).count() < 5 {
-        let ret_string = format!(r#"{{"result":"naughty","reason":"55555"}}"#);
-        return Ok((StatusCode::BAD_REQUEST, ret_string));
-    }
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    /// Stop at the first failing rule.
+    #[default]
+    First,
+    /// Evaluate every rule and report all failures.
+    All,
+}
 
-    let sum_digits: u32 = input
-        .input
-        .split(|c: char| !c.is_numeric())
-        .filter_map(|x| x.parse::<u32>().ok())
-        .sum::<u32>();
+#[derive(Debug, Default, Deserialize)]
+struct ModeQuery {
+    #[serde(default)]
+    mode: Mode,
+}
 
-    if sum_digits != 2023u32 {
-        let ret_string = format!(r#"{{"result":"naughty","reason":"math is hard"}}"#);
-        return Ok((StatusCode::BAD_REQUEST, ret_string));
-    }
+#[derive(Debug, Serialize)]
+struct Report {
+    result: Outcome,
+    failures: Vec<Failure>,
+    passed: usize,
+    total: usize,
+    /// Percentage of rules passed.
+    score: u8,
+    strength: Strength,
+}
 
-    let joy_vec: Vec<char> = input
-        .input
-        .chars()
-        .filter(|c| (*c == 'j' || *c == 'o' || *c == 'y'))
-        .collect();
-    let joy_dir = joy_vec
-        .windows(3)
-        .all(|s| s[0] == 'j' && s[1] == 'o' && s[2] == 'y');
-    println!("{} {:?}", joy_dir, joy_vec);
-    if !joy_dir || joy_vec.len() < 3 {
-        let ret_string = format!(r#"{{"result":"naughty","reason":"not joyful enough"}}"#);
-        return Ok((StatusCode::NOT_ACCEPTABLE, ret_string));
-    }
+/// Answers with every failure; the status is that of the first one.
+fn report(input: &str, failures: Vec<Failure>, total: usize) -> Response {
+    let status = failures
+        .first()
+        .map(|failure| StatusCode::from_u16(failure.status).unwrap_or(StatusCode::BAD_REQUEST))
+        .unwrap_or(StatusCode::OK);
+    let passed = total - failures.len();
+    let report = Report {
+        result: Outcome::from_nice(failures.is_empty()),
+        failures,
+        passed,
+        total,
+        score: (passed * 100).checked_div(total).unwrap_or(100) as u8,
+        strength: Strength::estimate(input),
+    };
+    (status, Json(report)).into_response()
+}
 
-    let sandwich = input
-        .input
-        .chars()
-        .collect::<Vec<char>>()
-        .windows(3)
-        .any(|s| s[0] == s[2] && s[1] != s[0] && s[1].is_alphabetic() && s[0].is_alphabetic());
-    if !sandwich {
-        let ret_string = format!(r#"{{"result":"naughty","reason":"illegal: no sandwich"}}"#);
-        return Ok((StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS, ret_string));
+async fn nice(Query(query): Query<ModeQuery>, Input(input): Input) -> Response {
+    if let Mode::All = query.mode {
+        let total = input.checks().len();
+        return report(&input.input, input.failures(), total);
     }
 
-    let range = input
-        .input
-        .chars()
-        .any(|c| (c as u32) > (0x00002980 as u32) && (c as u32) < (0x00002BFF as u32));
-    if !range {
-        let ret_string = format!(r#"{{"result":"naughty","reason":"outranged"}}"#);
-        return Ok((StatusCode::RANGE_NOT_SATISFIABLE, ret_string));
-    }
+    let nice = input.is_nice();
+    let status = if nice {
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    let verdict = Verdict {
+        result: Outcome::from_nice(nice),
+        reason: None,
+    };
+    (status, Json(verdict)).into_response()
+}
 
-    let emoji = input.input.chars().any(|c| {
-        (c as u32) > (0x00001F600 as u32) && (c as u32) < (0x00001F64F as u32)
-            || ((c as u32) > (0x00001F900 as u32) && (c as u32) < (0x00001F9FF as u32))
-    });
-    if !emoji {
-        let ret_string = format!(r#"{{"result":"naughty","reason":"😳"}}"#);
-        return Ok((StatusCode::UPGRADE_REQUIRED, ret_string));
-    }
+fn verdict(result: Result<(), Failure>) -> Response {
+    let (status, verdict) = match result {
+        Ok(()) => (
+            StatusCode::OK,
+            Verdict {
+                result: Outcome::Nice,
+                reason: Some("that's a nice password".to_string()),
+            },
+        ),
+        Err(Failure { status, reason, .. }) => (
+            StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_REQUEST),
+            Verdict {
+                result: Outcome::Naughty,
+                reason: Some(reason),
+            },
+        ),
+    };
+    (status, Json(verdict)).into_response()
+}
 
-    let hash = Sha256::digest(input.input.as_bytes());
-    let last_hex_char = format!("{:x}", hash)
-        .chars()
-        .nth_back(0)
-        .is_some_and(|c| c == 'a');
-    if !last_hex_char {
-        let ret_string = format!(r#"{{"result":"naughty","reason":"not a coffee brewer"}}"#);
-        return Ok((StatusCode::IM_A_TEAPOT, ret_string));
+fn apply(policy: &Policy, corpus: &SharedCorpus, input: &str, mode: Mode) -> Response {
+    let corpus = corpus.as_deref();
+    match mode {
+        Mode::First => verdict(policy.evaluate_with(input, corpus)),
+        Mode::All => report(
+            input,
+            policy.evaluate_all_with(input, corpus),
+            policy.rules.len(),
+        ),
     }
+}
 
-    let ret_string = format!(r#"{{"result":"nice","reason":"that's a nice password"}}"#);
-    return Ok((StatusCode::OK, ret_string));
+async fn game(
+    Query(query): Query<ModeQuery>,
+    State(policies): State<SharedPolicies>,
+    State(corpus): State<SharedCorpus>,
+    Input(input): Input,
+) -> Result<Response, AppError> {
+    let policy = policies
+        .get(GAME_POLICY)
+        .ok_or_else(|| AppError::PolicyNotFound(GAME_POLICY.to_string()))?;
+    Ok(apply(policy, &corpus, &input.input, query.mode))
 }
 
-pub fn router() -> Router {
-    Router::new()
-        .route("/nice", post(nice))
-        .route("/game", post(game))
+async fn evaluate_policy(
+    Path(name): Path<String>,
+    Query(query): Query<ModeQuery>,
+    State(policies): State<SharedPolicies>,
+    State(corpus): State<SharedCorpus>,
+    Input(input): Input,
+) -> Result<Response, AppError> {
+    let policy = policies
+        .get(&name)
+        .ok_or_else(|| AppError::PolicyNotFound(name.clone()))?;
+    Ok(apply(policy, &corpus, &input.input, query.mode))
+}
+
+async fn list_policies(State(policies): State<SharedPolicies>) -> impl IntoResponse {
+    let listed: BTreeMap<&str, &Policy> = policies
+        .names()
+        .filter_map(|name| policies.get(name).map(|policy| (name, policy)))
+        .collect();
+    Json(listed).into_response()
+}
+
+/// Breached hashes sharing a 5-digit SHA-1 prefix, one `SUFFIX:COUNT` per
+/// line, so a client can check a password without sending its full hash.
+async fn hash_range(
+    Path(prefix): Path<String>,
+    State(corpus): State<SharedCorpus>,
+) -> Result<String, AppError> {
+    if !is_prefix(&prefix) {
+        return Err(AppError::InvalidPrefix(prefix));
+    }
+    let corpus = corpus.ok_or(AppError::CorpusUnavailable)?;
+    Ok(corpus
+        .range(&prefix.to_ascii_uppercase())
+        .into_iter()
+        .map(|(suffix, count)| format!("{suffix}:{count}\r\n"))
+        .collect())
+}
+
+pub const DAY: Day = Day {
+    prefix: "/15",
+    title: "Password validation",
+    router,
+};
+
+pub fn router() -> Routes {
+    let policies = Arc::new(PolicySet::from_env().expect("failed to load day15 policies"));
+    let corpus = BreachCorpus::from_env()
+        .expect("failed to open day15 breach corpus")
+        .map(Arc::new);
+    Routes::new()
+        .post("/nice", nice)
+        .post("/game", game)
+        .post("/policy/:name", evaluate_policy)
+        .get("/policies", list_policies)
+        .get("/range/:prefix", hash_range)
+        .with_state(Day15State { policies, corpus })
 }
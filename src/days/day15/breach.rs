@@ -0,0 +1,125 @@
+use memmap2::Mmap;
+use sha1::{Digest, Sha1};
+use std::{cmp::Ordering, env, fs::File, io, path::Path};
+
+/// Environment variable pointing at a breached-password corpus.
+pub const BREACH_CORPUS_ENV: &str = "DAY15_BREACH_CORPUS";
+
+/// Length of the hash prefix a `/15/range` lookup is keyed by.
+pub const PREFIX_LEN: usize = 5;
+const HASH_LEN: usize = 40;
+
+/// Uppercase hex SHA-1 of a password, as the corpus stores it.
+pub fn sha1_hex(password: &str) -> String {
+    format!("{:X}", Sha1::digest(password.as_bytes()))
+}
+
+/// A memory-mapped corpus of breached password hashes.
+///
+/// The file holds one `SHA1:COUNT` line per password, with the 40 hex digits
+/// in uppercase and the lines sorted by hash, which is how the Pwned
+/// Passwords downloads are published. Nothing is read up front: lookups
+/// binary-search the mapping, so they touch `O(log n)` pages however large
+/// the corpus is.
+pub struct BreachCorpus {
+    map: Option<Mmap>,
+}
+
+impl BreachCorpus {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            return Ok(BreachCorpus { map: None });
+        }
+        // SAFETY: the corpus is read-only input; truncating it while the
+        // service runs is unsupported.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(BreachCorpus { map: Some(map) })
+    }
+
+    /// The corpus named by [`BREACH_CORPUS_ENV`], if one is configured.
+    pub fn from_env() -> io::Result<Option<Self>> {
+        match env::var(BREACH_CORPUS_ENV) {
+            Ok(path) => BreachCorpus::open(Path::new(&path)).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or_default()
+    }
+
+    /// How often `password` appears in the corpus; zero if it doesn't.
+    pub fn count(&self, password: &str) -> u64 {
+        let hash = sha1_hex(password);
+        self.lines_from(hash.as_bytes())
+            .next()
+            .filter(|(line_hash, _)| *line_hash == hash.as_bytes())
+            .map_or(0, |(_, count)| count)
+    }
+
+    /// Every hash starting with `prefix`, as `(suffix, count)` pairs.
+    ///
+    /// `prefix` must be uppercase hex; callers only ever send this part of
+    /// the hash, never the password itself.
+    pub fn range(&self, prefix: &str) -> Vec<(String, u64)> {
+        let prefix = prefix.as_bytes();
+        self.lines_from(prefix)
+            .take_while(|(hash, _)| hash.starts_with(prefix))
+            .map(|(hash, count)| {
+                let suffix = String::from_utf8_lossy(&hash[prefix.len()..]).into_owned();
+                (suffix, count)
+            })
+            .collect()
+    }
+
+    /// Parsed lines starting at the first hash that isn't less than `key`.
+    fn lines_from<'a>(&'a self, key: &[u8]) -> impl Iterator<Item = (&'a [u8], u64)> + 'a {
+        let data = self.data();
+        data[self.lower_bound(key)..]
+            .split(|&b| b == b'\n')
+            .filter_map(parse_line)
+    }
+
+    /// Byte offset of the first line whose hash, cut to `key.len()`, is not
+    /// less than `key`.
+    fn lower_bound(&self, key: &[u8]) -> usize {
+        let data = self.data();
+        // Both bounds always sit on a line start (or the end of the data).
+        let (mut lo, mut hi) = (0, data.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let start = data[lo..mid]
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(lo, |i| lo + i + 1);
+            let end = data[start..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(data.len(), |i| start + i);
+            let line = &data[start..end];
+            let hash = &line[..line.len().min(key.len())];
+            match hash.cmp(key) {
+                Ordering::Less => lo = (end + 1).min(data.len()),
+                _ => hi = start,
+            }
+        }
+        lo
+    }
+}
+
+fn parse_line(line: &[u8]) -> Option<(&[u8], u64)> {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let colon = line.iter().position(|&b| b == b':')?;
+    let (hash, count) = (&line[..colon], &line[colon + 1..]);
+    if hash.len() != HASH_LEN {
+        return None;
+    }
+    let count = std::str::from_utf8(count).ok()?.trim().parse().ok()?;
+    Some((hash, count))
+}
+
+/// Whether `prefix` is a valid `/15/range` key.
+pub fn is_prefix(prefix: &str) -> bool {
+    prefix.len() == PREFIX_LEN && prefix.bytes().all(|b| b.is_ascii_hexdigit())
+}
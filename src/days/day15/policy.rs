@@ -0,0 +1,387 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, env, fs, path::Path};
+use thiserror::Error;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::breach::BreachCorpus;
+
+/// Environment variable pointing at a directory of `<name>.json` policies.
+pub const POLICY_DIR_ENV: &str = "DAY15_POLICY_DIR";
+const DEFAULT_POLICY_DIR: &str = "policies/day15";
+
+/// Name of the built-in policy behind `/15/game`.
+pub const GAME_POLICY: &str = "game";
+
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    #[error("Policy {0} could not be read: {1}")]
+    Io(String, std::io::Error),
+    #[error("Policy {0} is invalid: {1}")]
+    Parse(String, serde_json::Error),
+}
+
+/// One structural check on a password.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum Rule {
+    MinLength {
+        min: usize,
+    },
+    /// Requires at least one character from each enabled class.
+    CharClasses {
+        #[serde(default = "enabled")]
+        upper: bool,
+        #[serde(default = "enabled")]
+        lower: bool,
+        #[serde(default = "enabled")]
+        digit: bool,
+    },
+    DigitCount {
+        min: usize,
+    },
+    /// The integers embedded in the password must add up to `sum`.
+    DigitSum {
+        sum: u32,
+    },
+    /// The letters `j`, `o` and `y` must appear in exactly that order.
+    Joy,
+    /// Some letter must appear twice with a different letter in between.
+    Sandwich,
+    UnicodeRange {
+        start: u32,
+        end: u32,
+    },
+    Emoji,
+    /// The lowercase hex SHA-256 digest must end with `suffix`.
+    Sha256Suffix {
+        suffix: String,
+    },
+    /// The password may appear in the breach corpus at most `max_count` times.
+    /// Fails when no corpus is loaded, since the password can't be cleared.
+    NotBreached {
+        #[serde(default)]
+        max_count: u64,
+    },
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Emoticons (U+1F600–U+1F64F) and Supplemental Symbols and Pictographs
+/// (U+1F900–U+1F9FF), both inclusive.
+pub fn is_emoji(c: char) -> bool {
+    matches!(c, '\u{1F600}'..='\u{1F64F}' | '\u{1F900}'..='\u{1F9FF}')
+}
+
+/// Whether a grapheme cluster starts with an alphabetic base character.
+pub fn is_letter(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_alphabetic)
+}
+
+impl Rule {
+    /// Checks `input` without a breach corpus.
+    pub fn passes(&self, input: &str) -> bool {
+        self.check(input, None)
+    }
+
+    pub fn check(&self, input: &str, corpus: Option<&BreachCorpus>) -> bool {
+        match self {
+            Rule::MinLength { min } => input.graphemes(true).count() >= *min,
+            Rule::CharClasses {
+                upper,
+                lower,
+                digit,
+            } => {
+                (!upper || input.chars().any(|c| c.is_uppercase()))
+                    && (!lower || input.chars().any(|c| c.is_lowercase()))
+                    && (!digit || input.chars().any(|c| c.is_ascii_digit()))
+            }
+            Rule::DigitCount { min } => {
+                input.chars().filter(|c| c.is_ascii_digit()).count() >= *min
+            }
+            Rule::DigitSum { sum } => {
+                // Runs too long for a u64 can't add up to any u32 target.
+                let sum_digits = input
+                    .split(|c: char| !c.is_ascii_digit())
+                    .filter(|x| !x.is_empty())
+                    .map(|x| x.parse::<u64>().unwrap_or(u64::MAX))
+                    .fold(0u64, u64::saturating_add);
+                sum_digits == u64::from(*sum)
+            }
+            Rule::Joy => {
+                let joy_vec: Vec<&str> = input
+                    .graphemes(true)
+                    .filter(|g| matches!(*g, "j" | "o" | "y"))
+                    .collect();
+                joy_vec == ["j", "o", "y"]
+            }
+            Rule::Sandwich => {
+                let graphemes: Vec<&str> = input.graphemes(true).collect();
+                graphemes
+                    .windows(3)
+                    .any(|s| s[0] == s[2] && s[1] != s[0] && is_letter(s[0]) && is_letter(s[1]))
+            }
+            Rule::UnicodeRange { start, end } => {
+                input.chars().any(|c| (*start..=*end).contains(&(c as u32)))
+            }
+            Rule::Emoji => input.chars().any(is_emoji),
+            Rule::Sha256Suffix { suffix } => {
+                let hash = Sha256::digest(input.as_bytes());
+                format!("{:x}", hash).ends_with(suffix.as_str())
+            }
+            Rule::NotBreached { max_count } => {
+                corpus.is_some_and(|corpus| corpus.count(input) <= *max_count)
+            }
+        }
+    }
+
+    /// Stable machine-readable code for this rule.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rule::MinLength { .. } => "min_length",
+            Rule::CharClasses { .. } => "char_classes",
+            Rule::DigitCount { .. } => "digit_count",
+            Rule::DigitSum { .. } => "digit_sum",
+            Rule::Joy => "joy",
+            Rule::Sandwich => "sandwich",
+            Rule::UnicodeRange { .. } => "unicode_range",
+            Rule::Emoji => "emoji",
+            Rule::Sha256Suffix { .. } => "sha256_suffix",
+            Rule::NotBreached { .. } => "not_breached",
+        }
+    }
+
+    fn default_status(&self) -> u16 {
+        match self {
+            Rule::MinLength { .. }
+            | Rule::CharClasses { .. }
+            | Rule::DigitCount { .. }
+            | Rule::DigitSum { .. }
+            | Rule::NotBreached { .. } => 400,
+            Rule::Joy => 406,
+            Rule::Sandwich => 451,
+            Rule::UnicodeRange { .. } => 416,
+            Rule::Emoji => 426,
+            Rule::Sha256Suffix { .. } => 418,
+        }
+    }
+
+    fn default_reason(&self) -> String {
+        match self {
+            Rule::MinLength { min } => format!("{min} chars"),
+            Rule::CharClasses { .. } => "more types of chars".to_string(),
+            Rule::DigitCount { min } => format!("at least {min} digits"),
+            Rule::DigitSum { .. } => "math is hard".to_string(),
+            Rule::Joy => "not joyful enough".to_string(),
+            Rule::Sandwich => "illegal: no sandwich".to_string(),
+            Rule::UnicodeRange { .. } => "outranged".to_string(),
+            Rule::Emoji => "😳".to_string(),
+            Rule::Sha256Suffix { .. } => "not a coffee brewer".to_string(),
+            Rule::NotBreached { .. } => "found in a data breach".to_string(),
+        }
+    }
+}
+
+/// A rule together with what to answer when it fails.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    #[serde(flatten)]
+    pub rule: Rule,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl PolicyRule {
+    fn new(rule: Rule) -> Self {
+        PolicyRule {
+            rule,
+            status: None,
+            reason: None,
+        }
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status.unwrap_or_else(|| self.rule.default_status())
+    }
+
+    pub fn reason(&self) -> String {
+        self.reason
+            .clone()
+            .unwrap_or_else(|| self.rule.default_reason())
+    }
+
+    fn failure(&self, corpus: Option<&BreachCorpus>) -> Failure {
+        let reason = match (&self.rule, corpus) {
+            (Rule::NotBreached { .. }, None) => "no breach corpus to check against".to_string(),
+            _ => self.reason(),
+        };
+        Failure {
+            rule: self.rule.code().to_string(),
+            status: self.status(),
+            reason,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Failure {
+    pub rule: String,
+    pub status: u16,
+    pub reason: String,
+}
+
+/// An ordered list of rules; the first one that fails decides the answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Policy {
+    pub rules: Vec<PolicyRule>,
+}
+
+impl Policy {
+    /// The rule chain `/15/game` has always applied.
+    pub fn game() -> Self {
+        Policy {
+            rules: vec![
+                PolicyRule::new(Rule::MinLength { min: 8 }),
+                PolicyRule::new(Rule::CharClasses {
+                    upper: true,
+                    lower: true,
+                    digit: true,
+                }),
+                PolicyRule {
+                    reason: Some("55555".to_string()),
+                    ..PolicyRule::new(Rule::DigitCount { min: 5 })
+                },
+                PolicyRule::new(Rule::DigitSum { sum: 2023 }),
+                PolicyRule::new(Rule::Joy),
+                PolicyRule::new(Rule::Sandwich),
+                PolicyRule::new(Rule::UnicodeRange {
+                    start: 0x2980,
+                    end: 0x2bff,
+                }),
+                PolicyRule::new(Rule::Emoji),
+                PolicyRule::new(Rule::Sha256Suffix {
+                    suffix: "a".to_string(),
+                }),
+            ],
+        }
+    }
+
+    pub fn evaluate(&self, input: &str) -> Result<(), Failure> {
+        self.evaluate_with(input, None)
+    }
+
+    pub fn evaluate_with(&self, input: &str, corpus: Option<&BreachCorpus>) -> Result<(), Failure> {
+        match self
+            .rules
+            .iter()
+            .find(|rule| !rule.rule.check(input, corpus))
+        {
+            Some(rule) => Err(rule.failure(corpus)),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks every rule instead of stopping at the first failure.
+    pub fn evaluate_all(&self, input: &str) -> Vec<Failure> {
+        self.evaluate_all_with(input, None)
+    }
+
+    pub fn evaluate_all_with(&self, input: &str, corpus: Option<&BreachCorpus>) -> Vec<Failure> {
+        self.rules
+            .iter()
+            .filter(|rule| !rule.rule.check(input, corpus))
+            .map(|rule| rule.failure(corpus))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strength {
+    VeryWeak,
+    Weak,
+    Fair,
+    Strong,
+    VeryStrong,
+}
+
+/// Rough brute-force entropy: length times log2 of the alphabet the password
+/// draws from, judged by which character classes appear in it.
+pub fn entropy_bits(input: &str) -> f64 {
+    let has = |class: fn(&char) -> bool, size: u32| {
+        if input.chars().any(|c| class(&c)) {
+            size
+        } else {
+            0
+        }
+    };
+    let pool = has(char::is_ascii_lowercase, 26)
+        + has(char::is_ascii_uppercase, 26)
+        + has(char::is_ascii_digit, 10)
+        + has(|c| c.is_ascii_punctuation() || *c == ' ', 33)
+        + has(|c| !c.is_ascii(), 100);
+    if pool == 0 {
+        return 0.0;
+    }
+    input.chars().count() as f64 * f64::from(pool).log2()
+}
+
+impl Strength {
+    pub fn estimate(input: &str) -> Self {
+        match entropy_bits(input) {
+            bits if bits < 28.0 => Strength::VeryWeak,
+            bits if bits < 36.0 => Strength::Weak,
+            bits if bits < 60.0 => Strength::Fair,
+            bits if bits < 128.0 => Strength::Strong,
+            _ => Strength::VeryStrong,
+        }
+    }
+}
+
+/// Every policy that can be evaluated by name.
+pub struct PolicySet {
+    policies: BTreeMap<String, Policy>,
+}
+
+impl PolicySet {
+    /// The built-in policies plus every `<name>.json` in `dir`, if it exists.
+    pub fn load(dir: &Path) -> Result<Self, PolicyError> {
+        let mut policies = BTreeMap::from([(GAME_POLICY.to_string(), Policy::game())]);
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(PolicySet { policies });
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let Some(name) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .filter(|_| path.extension().is_some_and(|ext| ext == "json"))
+            else {
+                continue;
+            };
+            let source =
+                fs::read_to_string(&path).map_err(|e| PolicyError::Io(name.to_string(), e))?;
+            let policy = serde_json::from_str(&source)
+                .map_err(|e| PolicyError::Parse(name.to_string(), e))?;
+            policies.insert(name.to_string(), policy);
+        }
+        Ok(PolicySet { policies })
+    }
+
+    pub fn from_env() -> Result<Self, PolicyError> {
+        let dir = env::var(POLICY_DIR_ENV).unwrap_or(DEFAULT_POLICY_DIR.to_string());
+        PolicySet::load(Path::new(&dir))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Policy> {
+        self.policies.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.policies.keys().map(String::as_str)
+    }
+}
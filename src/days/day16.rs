@@ -0,0 +1,108 @@
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use json_patch::{Patch, PatchError, PatchOperation};
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::error::Problem;
+use crate::extract::Json;
+use crate::registry::{Day, Routes};
+
+const JSON_PATCH: &str = "application/json-patch+json";
+
+#[derive(Error, Debug)]
+enum AppError {
+    #[error("Operation {} ({} at {:?}) failed: {}", .0.operation, .1, .0.path.as_str(), .0.kind)]
+    PatchFailed(PatchError, &'static str),
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let detail = self.to_string();
+        match self {
+            // `operation` is the index of the failed operation in the patch.
+            AppError::PatchFailed(e, op) => {
+                Problem::new(StatusCode::CONFLICT, "patch_failed", detail)
+                    .with("operation", e.operation)
+                    .with("op", op)
+                    .with("path", e.path.as_str())
+                    .into_response()
+            }
+        }
+    }
+}
+
+fn op_name(operation: &PatchOperation) -> &'static str {
+    match operation {
+        PatchOperation::Add(_) => "add",
+        PatchOperation::Remove(_) => "remove",
+        PatchOperation::Replace(_) => "replace",
+        PatchOperation::Move(_) => "move",
+        PatchOperation::Copy(_) => "copy",
+        PatchOperation::Test(_) => "test",
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DiffRequest {
+    from: Value,
+    to: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct PatchRequest {
+    document: Value,
+    patch: Patch,
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeRequest {
+    document: Value,
+    patch: Value,
+}
+
+/// The RFC 6902 patch that turns `from` into `to`.
+async fn diff(Json(request): Json<DiffRequest>) -> impl IntoResponse {
+    let patch = json_patch::diff(&request.from, &request.to);
+    ([(header::CONTENT_TYPE, JSON_PATCH)], Json(patch))
+}
+
+/// Applies an RFC 6902 patch; either every operation succeeds or the
+/// document is left as it was.
+async fn patch(Json(request): Json<PatchRequest>) -> Result<Json<Value>, AppError> {
+    let PatchRequest {
+        mut document,
+        patch,
+    } = request;
+    json_patch::patch(&mut document, &patch).map_err(|e| {
+        let op = patch.get(e.operation).map_or("unknown", op_name);
+        AppError::PatchFailed(e, op)
+    })?;
+    Ok(Json(document))
+}
+
+/// Applies an RFC 7386 merge patch, which can't fail.
+async fn merge(Json(request): Json<MergeRequest>) -> Json<Value> {
+    let MergeRequest {
+        mut document,
+        patch,
+    } = request;
+    json_patch::merge(&mut document, &patch);
+    Json(document)
+}
+
+pub const DAY: Day = Day {
+    prefix: "/16",
+    title: "JSON diff, patch and merge",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new()
+        .post("/diff", diff)
+        .post("/patch", patch)
+        .post("/merge", merge)
+}
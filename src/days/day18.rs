@@ -0,0 +1,182 @@
+use std::env;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, SqlitePool};
+use thiserror::Error;
+
+use super::day13::{self, Order, CREATE_ORDERS};
+use crate::error::Problem;
+use crate::extract::{Json, Path};
+use crate::registry::{Day, Routes};
+
+/// Environment variable overriding the SQLite database URL.
+pub const DATABASE_URL_ENV: &str = "DAY18_DATABASE_URL";
+const DEFAULT_DATABASE_URL: &str = "sqlite::memory:";
+
+const CREATE_REGIONS: &str = "CREATE TABLE IF NOT EXISTS regions (
+    id INT PRIMARY KEY,
+    name VARCHAR(50)
+)";
+
+#[derive(Error, Debug)]
+enum AppError {
+    #[error("An order or region with that id already exists")]
+    Duplicate,
+    #[error("Database error")]
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        if day13::is_duplicate(&e) {
+            AppError::Duplicate
+        } else {
+            AppError::Database(e)
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, code) = match &self {
+            AppError::Duplicate => (StatusCode::CONFLICT, "duplicate_id"),
+            AppError::Database(e) => {
+                tracing::error!(error = %e, "day18 database error");
+                (StatusCode::INTERNAL_SERVER_ERROR, "database")
+            }
+        };
+
+        Problem::new(status, code, self.to_string()).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Region {
+    id: i64,
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RegionTotal {
+    region: String,
+    total: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct TopList {
+    region: String,
+    top_gifts: Vec<String>,
+}
+
+async fn reset(State(pool): State<SqlitePool>) -> Result<StatusCode, AppError> {
+    let mut tx = pool.begin().await?;
+    tx.execute("DROP TABLE IF EXISTS regions").await?;
+    tx.execute("DROP TABLE IF EXISTS orders").await?;
+    tx.execute(CREATE_REGIONS).await?;
+    tx.execute(CREATE_ORDERS).await?;
+    tx.commit().await?;
+    Ok(StatusCode::OK)
+}
+
+async fn insert_orders(
+    State(pool): State<SqlitePool>,
+    Json(orders): Json<Vec<Order>>,
+) -> Result<StatusCode, AppError> {
+    day13::insert(&pool, orders).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn insert_regions(
+    State(pool): State<SqlitePool>,
+    Json(regions): Json<Vec<Region>>,
+) -> Result<StatusCode, AppError> {
+    let mut tx = pool.begin().await?;
+    for region in regions {
+        sqlx::query("INSERT INTO regions (id, name) VALUES (?, ?)")
+            .bind(region.id)
+            .bind(region.name)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(StatusCode::OK)
+}
+
+/// Total quantity ordered per region, by region name; regions without
+/// orders are left out.
+async fn regions_total(State(pool): State<SqlitePool>) -> Result<Json<Vec<RegionTotal>>, AppError> {
+    let totals: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT regions.name, SUM(orders.quantity)
+         FROM orders JOIN regions ON regions.id = orders.region_id
+         GROUP BY regions.id
+         ORDER BY regions.name, regions.id",
+    )
+    .fetch_all(&pool)
+    .await?;
+    Ok(Json(
+        totals
+            .into_iter()
+            .map(|(region, total)| RegionTotal { region, total })
+            .collect(),
+    ))
+}
+
+/// The `number` most ordered gifts of every region, ties broken by gift name.
+async fn regions_top_list(
+    Path(number): Path<u32>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<TopList>>, AppError> {
+    let rows: Vec<(i64, String, Option<String>)> = sqlx::query_as(
+        "SELECT regions.id, regions.name, ranked.gift_name
+         FROM regions LEFT JOIN (
+             SELECT region_id, gift_name, ROW_NUMBER() OVER (
+                 PARTITION BY region_id ORDER BY SUM(quantity) DESC, gift_name
+             ) AS rank
+             FROM orders GROUP BY region_id, gift_name
+         ) AS ranked ON ranked.region_id = regions.id AND ranked.rank <= ?
+         ORDER BY regions.name, regions.id, ranked.rank",
+    )
+    .bind(number)
+    .fetch_all(&pool)
+    .await?;
+
+    let mut lists: Vec<(i64, TopList)> = Vec::new();
+    for (id, region, gift_name) in rows {
+        if lists.last().is_none_or(|(last, _)| *last != id) {
+            let top_gifts = Vec::new();
+            lists.push((id, TopList { region, top_gifts }));
+        }
+        if let (Some(gift_name), Some((_, list))) = (gift_name, lists.last_mut()) {
+            list.top_gifts.push(gift_name);
+        }
+    }
+    Ok(Json(lists.into_iter().map(|(_, list)| list).collect()))
+}
+
+pub const DAY: Day = Day {
+    prefix: "/18",
+    title: "Regional order analytics",
+    router,
+};
+
+pub fn router_with(pool: SqlitePool) -> Routes {
+    Routes::new()
+        .post("/reset", reset)
+        .post("/orders", insert_orders)
+        .post("/regions", insert_regions)
+        .get("/regions/total", regions_total)
+        .get("/regions/top_list/:number", regions_top_list)
+        .with_state(pool)
+}
+
+pub fn router() -> Routes {
+    let url = env::var(DATABASE_URL_ENV).unwrap_or(DEFAULT_DATABASE_URL.to_string());
+    let pool =
+        day13::connect(&url, &[CREATE_REGIONS, CREATE_ORDERS]).expect("invalid day18 database URL");
+    router_with(pool)
+}
@@ -0,0 +1,190 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::StatusCode,
+    response::IntoResponse,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::extract::Path;
+use crate::registry::{Day, Routes};
+
+/// Longest chat message, in characters, that gets delivered.
+const MAX_MESSAGE_CHARS: usize = 128;
+/// Messages a slow reader may fall behind before it starts skipping some.
+const ROOM_CAPACITY: usize = 256;
+
+#[derive(Default)]
+struct Day19State {
+    rooms: Mutex<HashMap<u32, broadcast::Sender<Arc<str>>>>,
+    /// Chat messages delivered, counted once per recipient.
+    views: AtomicU64,
+}
+
+type SharedState = Arc<Day19State>;
+
+impl Day19State {
+    fn join(&self, room: u32) -> (broadcast::Sender<Arc<str>>, broadcast::Receiver<Arc<str>>) {
+        let mut rooms = self.rooms.lock().unwrap();
+        let sender = rooms
+            .entry(room)
+            .or_insert_with(|| broadcast::channel(ROOM_CAPACITY).0);
+        (sender.clone(), sender.subscribe())
+    }
+
+    /// Forgets the room once its last member has left.
+    fn leave(&self, room: u32) {
+        let mut rooms = self.rooms.lock().unwrap();
+        if rooms
+            .get(&room)
+            .is_some_and(|sender| sender.receiver_count() == 0)
+        {
+            rooms.remove(&room);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct IncomingTweet {
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Tweet<'a> {
+    user: &'a str,
+    message: &'a str,
+}
+
+async fn ping(ws: WebSocketUpgrade) -> impl IntoResponse {
+    ws.on_upgrade(play_ping)
+}
+
+/// Answers every `ping` with a `pong`, but only once the client has sent
+/// `serve`.
+async fn play_ping(mut socket: WebSocket) {
+    let mut serving = false;
+    while let Some(Ok(message)) = socket.recv().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let reply = match text.as_str() {
+            "serve" => {
+                serving = true;
+                None
+            }
+            "ping" if serving => Some("pong"),
+            _ => None,
+        };
+        if let Some(reply) = reply {
+            if socket.send(Message::Text(reply.to_string())).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+async fn room(
+    Path((room, user)): Path<(u32, String)>,
+    State(state): State<SharedState>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| chat(socket, state, room, user))
+}
+
+async fn chat(socket: WebSocket, state: SharedState, room: u32, user: String) {
+    let (sender, mut receiver) = state.join(room);
+    let (mut outgoing, mut incoming) = socket.split();
+
+    let delivery_state = state.clone();
+    let mut delivery = tokio::spawn(async move {
+        loop {
+            let tweet = match receiver.recv().await {
+                Ok(tweet) => tweet,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            if outgoing
+                .send(Message::Text(tweet.to_string()))
+                .await
+                .is_err()
+            {
+                break;
+            }
+            delivery_state.views.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    let mut delivering = true;
+    while delivering {
+        let message = tokio::select! {
+            message = incoming.next() => message,
+            _ = &mut delivery => {
+                delivering = false;
+                continue;
+            }
+        };
+        let Some(Ok(message)) = message else {
+            break;
+        };
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let Ok(IncomingTweet { message }) = serde_json::from_str(&text) else {
+            continue;
+        };
+        if message.chars().count() > MAX_MESSAGE_CHARS {
+            continue;
+        }
+        let tweet = serde_json::to_string(&Tweet {
+            user: &user,
+            message: &message,
+        })
+        .unwrap();
+        // Sending only fails without receivers, and this client is one.
+        let _ = sender.send(tweet.into());
+    }
+
+    if delivering {
+        delivery.abort();
+        // Waiting for the abort makes sure the receiver is gone before `leave`.
+        let _ = delivery.await;
+    }
+    drop(sender);
+    state.leave(room);
+}
+
+async fn views(State(state): State<SharedState>) -> String {
+    state.views.load(Ordering::Relaxed).to_string()
+}
+
+async fn reset(State(state): State<SharedState>) -> StatusCode {
+    state.views.store(0, Ordering::Relaxed);
+    StatusCode::OK
+}
+
+pub const DAY: Day = Day {
+    prefix: "/19",
+    title: "WebSocket ping and chat rooms",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new()
+        .get("/ws/ping", ping)
+        .get("/ws/room/:number/user/:string", room)
+        .get("/views", views)
+        .post("/reset", reset)
+        .with_state(SharedState::default())
+}
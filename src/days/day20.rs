@@ -0,0 +1,159 @@
+use std::{collections::HashSet, error::Error as StdError, io, path::Path};
+
+use axum::{
+    body::Bytes,
+    extract::DefaultBodyLimit,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use gix::revision::walk::Sorting;
+use gix::traverse::commit::simple::CommitTimeOrder;
+use gix::ObjectId;
+use tar::Archive;
+use thiserror::Error;
+
+use crate::error::Problem;
+use crate::registry::{Day, Routes};
+
+/// Largest archive accepted, in bytes.
+const MAX_ARCHIVE_SIZE: usize = 64 * 1024 * 1024;
+
+const COOKIE_BRANCH: &str = "refs/heads/christmas";
+const COOKIE_FILE: &str = "santa.txt";
+const COOKIE: &[u8] = b"COOKIE";
+
+type GitError = Box<dyn StdError + Send + Sync>;
+
+#[derive(Error, Debug)]
+enum AppError {
+    #[error("Invalid archive: {0}")]
+    Archive(#[from] io::Error),
+    #[error("Invalid repository: {0}")]
+    Git(GitError),
+    #[error("No commit on {COOKIE_BRANCH} has a {COOKIE_FILE} with a cookie in it")]
+    NoCookie,
+    #[error("Could not create a working directory")]
+    WorkDir(io::Error),
+    #[error("Archive inspection failed")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, code) = match self {
+            AppError::Archive(_) => (StatusCode::BAD_REQUEST, "invalid_archive"),
+            AppError::Git(_) => (StatusCode::BAD_REQUEST, "invalid_repository"),
+            AppError::NoCookie => (StatusCode::BAD_REQUEST, "no_cookie"),
+            AppError::WorkDir(ref e) => {
+                tracing::error!(error = %e, "day20 could not create a working directory");
+                (StatusCode::INTERNAL_SERVER_ERROR, "inspection_failed")
+            }
+            AppError::Join(_) => (StatusCode::INTERNAL_SERVER_ERROR, "inspection_failed"),
+        };
+
+        Problem::new(status, code, self.to_string()).into_response()
+    }
+}
+
+/// Sizes of the regular files in a tar archive.
+fn file_sizes(archive: &[u8]) -> io::Result<Vec<u64>> {
+    let mut sizes = Vec::new();
+    for entry in Archive::new(archive).entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() {
+            sizes.push(entry.size());
+        }
+    }
+    Ok(sizes)
+}
+
+async fn archive_files(archive: Bytes) -> Result<String, AppError> {
+    Ok(file_sizes(&archive)?.len().to_string())
+}
+
+async fn archive_files_size(archive: Bytes) -> Result<String, AppError> {
+    Ok(file_sizes(&archive)?.iter().sum::<u64>().to_string())
+}
+
+/// Whether the tree `root`, or any tree below it, has a cookie file with a
+/// cookie in it.
+///
+/// Walks with an explicit stack, since uploaded trees can nest arbitrarily
+/// deep. Trees in `searched` are skipped and every tree searched is added to
+/// it, so a subtree shared between paths or commits is only read once.
+fn has_cookie(
+    repo: &gix::Repository,
+    root: ObjectId,
+    searched: &mut HashSet<ObjectId>,
+) -> Result<bool, GitError> {
+    let mut pending = vec![root];
+    while let Some(id) = pending.pop() {
+        if !searched.insert(id) {
+            continue;
+        }
+        let tree = repo.find_tree(id)?;
+        for entry in tree.iter() {
+            let entry = entry?;
+            let mode = entry.mode();
+            if mode.is_tree() {
+                pending.push(entry.object_id());
+            } else if mode.is_blob() && entry.filename() == COOKIE_FILE {
+                let blob = entry.object()?;
+                if blob
+                    .data
+                    .windows(COOKIE.len())
+                    .any(|window| window == COOKIE)
+                {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Author and hash of the newest cookie commit in the repository at `dir`.
+fn find_cookie(dir: &Path) -> Result<Option<(String, String)>, GitError> {
+    let repo = gix::open(dir)?;
+    let tip = repo.find_reference(COOKIE_BRANCH)?.peel_to_commit()?;
+    let commits = tip
+        .ancestors()
+        .sorting(Sorting::ByCommitTime(CommitTimeOrder::NewestFirst))
+        .all()?;
+    let mut searched = HashSet::new();
+    for info in commits {
+        let commit = info?.object()?;
+        if has_cookie(&repo, commit.tree_id()?.detach(), &mut searched)? {
+            let author = commit.author()?.name.to_string();
+            return Ok(Some((author, commit.id.to_string())));
+        }
+    }
+    Ok(None)
+}
+
+/// Unpacks the archive, which holds a git repository, and names the author
+/// and hash of the newest commit on `christmas` that found the cookie.
+async fn cookie(archive: Bytes) -> Result<String, AppError> {
+    let found = tokio::task::spawn_blocking(move || {
+        let dir = tempfile::tempdir().map_err(AppError::WorkDir)?;
+        Archive::new(&archive[..]).unpack(dir.path())?;
+        find_cookie(dir.path()).map_err(AppError::Git)
+    })
+    .await??;
+    let (author, hash) = found.ok_or(AppError::NoCookie)?;
+    Ok(format!("{author} {hash}"))
+}
+
+pub const DAY: Day = Day {
+    prefix: "/20",
+    title: "Archives and git history",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new()
+        .post("/archive_files", archive_files)
+        .post("/archive_files_size", archive_files_size)
+        .post("/cookie", cookie)
+        .layer(DefaultBodyLimit::max(MAX_ARCHIVE_SIZE))
+}
@@ -0,0 +1,98 @@
+mod countries;
+mod s2;
+
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use thiserror::Error;
+
+use crate::error::Problem;
+use crate::extract::Path;
+use crate::registry::{Day, Routes};
+use countries::Countries;
+use s2::CellId;
+
+type SharedCountries = Arc<Countries>;
+
+#[derive(Error, Debug)]
+enum AppError {
+    #[error("{0} is not a 64-bit binary number")]
+    InvalidBinary(String),
+    #[error("{0:#066b} is not a valid S2 cell ID")]
+    InvalidCell(u64),
+    #[error("No country at {0}")]
+    NoCountry(String),
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, code) = match self {
+            AppError::InvalidBinary(_) => (StatusCode::BAD_REQUEST, "invalid_binary"),
+            AppError::InvalidCell(_) => (StatusCode::BAD_REQUEST, "invalid_cell"),
+            AppError::NoCountry(_) => (StatusCode::NOT_FOUND, "no_country"),
+        };
+
+        Problem::new(status, code, self.to_string()).into_response()
+    }
+}
+
+fn cell(binary: &str) -> Result<CellId, AppError> {
+    if binary.is_empty() || binary.len() > 64 {
+        return Err(AppError::InvalidBinary(binary.to_string()));
+    }
+    let id =
+        u64::from_str_radix(binary, 2).map_err(|_| AppError::InvalidBinary(binary.to_string()))?;
+    CellId::new(id).ok_or(AppError::InvalidCell(id))
+}
+
+/// Degrees, minutes and seconds to three decimals, e.g. `83°39'54.324''N`.
+fn dms(degrees: f64, positive: char, negative: char) -> String {
+    let hemisphere = if degrees < 0.0 { negative } else { positive };
+    // Work in thousandths of a second so rounding carries into the minutes.
+    let total = (degrees.abs() * 3_600_000.0).round() as u64;
+    let (whole, millis) = (total / 3_600_000, total % 3_600_000);
+    let (minutes, millis) = (millis / 60_000, millis % 60_000);
+    format!(
+        "{whole}°{minutes}'{}.{:03}''{hemisphere}",
+        millis / 1000,
+        millis % 1000
+    )
+}
+
+fn coordinates(lat: f64, lng: f64) -> String {
+    format!("{} {}", dms(lat, 'N', 'S'), dms(lng, 'E', 'W'))
+}
+
+async fn coords(Path(binary): Path<String>) -> Result<String, AppError> {
+    let (lat, lng) = cell(&binary)?.to_lat_lng();
+    Ok(coordinates(lat, lng))
+}
+
+async fn country(
+    Path(binary): Path<String>,
+    State(countries): State<SharedCountries>,
+) -> Result<String, AppError> {
+    let (lat, lng) = cell(&binary)?.to_lat_lng();
+    countries
+        .lookup(lat, lng)
+        .map(str::to_string)
+        .ok_or_else(|| AppError::NoCountry(coordinates(lat, lng)))
+}
+
+pub const DAY: Day = Day {
+    prefix: "/21",
+    title: "S2 cells and countries",
+    router,
+};
+
+pub fn router() -> Routes {
+    let countries = Countries::from_env().expect("failed to load day21 country dataset");
+    Routes::new()
+        .get("/coords/:binary", coords)
+        .get("/country/:binary", country)
+        .with_state(Arc::new(countries))
+}
@@ -0,0 +1,91 @@
+use std::{env, fs, io, path::Path};
+
+use geo::{BoundingRect, Contains, MultiPolygon, Point, Rect};
+use geojson::{FeatureCollection, GeoJson, Geometry};
+use thiserror::Error;
+
+/// Environment variable pointing at the country boundary dataset.
+pub const COUNTRIES_ENV: &str = "DAY21_COUNTRIES";
+/// A GeoJSON `FeatureCollection` with one (multi)polygon per country. The
+/// bundled one is world.geo.json's simplification of Natural Earth's public
+/// domain 1:110m admin-0 countries.
+const DEFAULT_COUNTRIES: &str = "assets/day21/countries.geojson";
+
+/// Feature properties tried, in order, for a country's name.
+const NAME_PROPERTIES: [&str; 4] = ["name", "NAME", "ADMIN", "NAME_EN"];
+
+#[derive(Error, Debug)]
+pub enum CountriesError {
+    #[error("Country dataset could not be read: {0}")]
+    Io(#[from] io::Error),
+    #[error("Country dataset is invalid: {0}")]
+    Parse(Box<geojson::Error>),
+    #[error("Country dataset is not a FeatureCollection")]
+    NotACollection,
+}
+
+struct Country {
+    name: String,
+    bounds: Rect,
+    shape: MultiPolygon,
+}
+
+/// Offline reverse geocoding: which country polygon contains a point.
+pub struct Countries {
+    countries: Vec<Country>,
+}
+
+fn shape(geometry: &Geometry) -> Option<MultiPolygon> {
+    match geometry.value {
+        geojson::Value::Polygon(_) => geo::Polygon::try_from(geometry.value.clone())
+            .ok()
+            .map(MultiPolygon::from),
+        geojson::Value::MultiPolygon(_) => MultiPolygon::try_from(geometry.value.clone()).ok(),
+        _ => None,
+    }
+}
+
+impl Countries {
+    /// Features without a name or a polygon are skipped.
+    pub fn parse(source: &str) -> Result<Self, CountriesError> {
+        let GeoJson::FeatureCollection(FeatureCollection { features, .. }) = source
+            .parse()
+            .map_err(|e| CountriesError::Parse(Box::new(e)))?
+        else {
+            return Err(CountriesError::NotACollection);
+        };
+        let countries = features
+            .iter()
+            .filter_map(|feature| {
+                let name = NAME_PROPERTIES
+                    .iter()
+                    .find_map(|key| feature.property(key)?.as_str())?;
+                let shape = shape(feature.geometry.as_ref()?)?;
+                Some(Country {
+                    name: name.to_string(),
+                    bounds: shape.bounding_rect()?,
+                    shape,
+                })
+            })
+            .collect();
+        Ok(Countries { countries })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CountriesError> {
+        Countries::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The dataset named by [`COUNTRIES_ENV`], or the bundled one.
+    pub fn from_env() -> Result<Self, CountriesError> {
+        let path = env::var(COUNTRIES_ENV).unwrap_or(DEFAULT_COUNTRIES.to_string());
+        Countries::load(Path::new(&path))
+    }
+
+    pub fn lookup(&self, lat: f64, lng: f64) -> Option<&str> {
+        let point = Point::new(lng, lat);
+        self.countries
+            .iter()
+            .find(|country| country.bounds.contains(&point) && country.shape.contains(&point))
+            .map(|country| country.name.as_str())
+    }
+}
@@ -0,0 +1,84 @@
+const MAX_LEVEL: u32 = 30;
+const POS_BITS: u32 = 2 * MAX_LEVEL + 1;
+
+/// Hilbert curve position to `(i << 1) | j`, for each of the four
+/// orientations a sub-square can have.
+const POS_TO_IJ: [[u8; 4]; 4] = [[0, 1, 3, 2], [0, 2, 3, 1], [3, 2, 0, 1], [3, 1, 0, 2]];
+/// How a sub-square's orientation differs from its parent's, by position.
+const POS_TO_ORIENTATION: [u8; 4] = [1, 0, 0, 3];
+
+/// A valid 64-bit S2 cell ID; only enough of the S2 hierarchy is
+/// implemented to find a cell's centre.
+#[derive(Debug, Clone, Copy)]
+pub struct CellId(u64);
+
+impl CellId {
+    /// `None` unless the ID names a face between 0 and 5 and ends in the
+    /// marker bit of some level.
+    pub fn new(id: u64) -> Option<Self> {
+        let lowest = id & id.wrapping_neg();
+        let valid = id >> POS_BITS < 6 && lowest & 0x1555_5555_5555_5555 != 0;
+        valid.then_some(CellId(id))
+    }
+
+    fn face(self) -> u8 {
+        (self.0 >> POS_BITS) as u8
+    }
+
+    /// Leaf-level `(i, j)` coordinates of the cell on its face, found by
+    /// walking down the Hilbert curve two bits at a time.
+    fn face_ij(self) -> (u32, u32) {
+        let (mut i, mut j) = (0u32, 0u32);
+        let mut orientation = self.face() & 1;
+        for level in 1..=MAX_LEVEL {
+            let pos = ((self.0 >> (POS_BITS - 2 * level)) & 3) as usize;
+            let ij = POS_TO_IJ[orientation as usize][pos];
+            i = (i << 1) | u32::from(ij >> 1);
+            j = (j << 1) | u32::from(ij & 1);
+            orientation ^= POS_TO_ORIENTATION[pos];
+        }
+        (i, j)
+    }
+
+    /// Latitude and longitude of the cell centre, in degrees.
+    pub fn to_lat_lng(self) -> (f64, f64) {
+        let (i, j) = self.face_ij();
+        let is_leaf = self.0 & 1 == 1;
+        // Leaves are centred half a leaf in; a larger cell's centre is the
+        // corner of leaves its walk ended on, one leaf further out or not.
+        let delta = if is_leaf {
+            1
+        } else if (u64::from(i) ^ (self.0 >> 2)) & 1 == 1 {
+            2
+        } else {
+            0
+        };
+        let scale = f64::from(1u32 << (MAX_LEVEL + 1));
+        let s = f64::from(2 * i + delta) / scale;
+        let t = f64::from(2 * j + delta) / scale;
+        let (x, y, z) = face_uv_to_xyz(self.face(), st_to_uv(s), st_to_uv(t));
+        let lat = z.atan2(x.hypot(y)).to_degrees();
+        let lng = y.atan2(x).to_degrees();
+        (lat, lng)
+    }
+}
+
+/// The quadratic projection S2 uses to even out cell areas.
+fn st_to_uv(s: f64) -> f64 {
+    if s >= 0.5 {
+        (4.0 * s * s - 1.0) / 3.0
+    } else {
+        (1.0 - 4.0 * (1.0 - s) * (1.0 - s)) / 3.0
+    }
+}
+
+fn face_uv_to_xyz(face: u8, u: f64, v: f64) -> (f64, f64, f64) {
+    match face {
+        0 => (1.0, u, v),
+        1 => (-u, 1.0, v),
+        2 => (-u, -v, 1.0),
+        3 => (-1.0, -v, -u),
+        4 => (v, -1.0, -u),
+        _ => (v, u, -1.0),
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+
+use axum::{
+    body::Body,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use futures_util::StreamExt;
+use thiserror::Error;
+
+use crate::error::Problem;
+use crate::registry::{Day, Routes};
+
+const PRESENT: &str = "🎁";
+/// Most presents a single answer may hold.
+const MAX_PRESENTS: u64 = 1 << 20;
+/// Longest line `/integers` accepts; a `u64` needs at most 20 digits.
+const MAX_LINE: usize = 64;
+
+#[derive(Error, Debug)]
+enum AppError {
+    #[error("Invalid number {0:?}")]
+    InvalidNumber(String),
+    #[error("Could not read request body: {0}")]
+    Body(#[from] axum::Error),
+    #[error("Invalid star map: {0}")]
+    InvalidMap(&'static str),
+    #[error("No portal path leads from the first star to the last")]
+    NoPath,
+    #[error("{0} presents is too many to wrap")]
+    TooManyPresents(u64),
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, code) = match self {
+            AppError::InvalidNumber(_) => (StatusCode::BAD_REQUEST, "invalid_number"),
+            AppError::Body(_) => (StatusCode::BAD_REQUEST, "unreadable_body"),
+            AppError::InvalidMap(_) => (StatusCode::BAD_REQUEST, "invalid_star_map"),
+            AppError::NoPath => (StatusCode::UNPROCESSABLE_ENTITY, "no_path"),
+            AppError::TooManyPresents(_) => (StatusCode::UNPROCESSABLE_ENTITY, "too_many_presents"),
+        };
+
+        Problem::new(status, code, self.to_string()).into_response()
+    }
+}
+
+fn parse_number(line: &[u8]) -> Result<u64, AppError> {
+    let invalid = || AppError::InvalidNumber(String::from_utf8_lossy(line).into_owned());
+    std::str::from_utf8(line)
+        .map_err(|_| invalid())?
+        .trim()
+        .parse()
+        .map_err(|_| invalid())
+}
+
+/// Every number appears twice except one, so XOR-ing them all leaves it.
+///
+/// The body is read a chunk at a time and only the current line is kept,
+/// so memory doesn't grow with the size of the list.
+async fn integers(body: Body) -> Result<String, AppError> {
+    let mut unpaired = 0;
+    let mut line = Vec::new();
+    let mut chunks = body.into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        for &byte in chunk?.iter() {
+            if byte == b'\n' {
+                if !line.trim_ascii().is_empty() {
+                    unpaired ^= parse_number(&line)?;
+                }
+                line.clear();
+            } else if line.len() < MAX_LINE {
+                line.push(byte);
+            } else {
+                return Err(AppError::InvalidNumber(
+                    String::from_utf8_lossy(&line).into_owned(),
+                ));
+            }
+        }
+    }
+    if !line.trim_ascii().is_empty() {
+        unpaired ^= parse_number(&line)?;
+    }
+    if unpaired > MAX_PRESENTS {
+        return Err(AppError::TooManyPresents(unpaired));
+    }
+    Ok(PRESENT.repeat(unpaired as usize))
+}
+
+type Star = [f64; 3];
+
+fn count<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    what: &'static str,
+) -> Result<usize, AppError> {
+    lines
+        .next()
+        .and_then(|line| line.parse().ok())
+        .ok_or(AppError::InvalidMap(what))
+}
+
+struct StarMap {
+    stars: Vec<Star>,
+    /// Portals leaving each star, both ways.
+    portals: Vec<Vec<usize>>,
+}
+
+impl StarMap {
+    /// A count of stars, one `x y z` line per star, then a count of portals
+    /// and one `a b` line per portal.
+    fn parse(input: &str) -> Result<Self, AppError> {
+        let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+        let star_count = count(&mut lines, "missing star count")?;
+        let mut stars = Vec::new();
+        for _ in 0..star_count {
+            let line = lines.next().ok_or(AppError::InvalidMap("missing star"))?;
+            let coordinates: Vec<f64> = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| AppError::InvalidMap("invalid star coordinates"))?;
+            let star = coordinates
+                .try_into()
+                .map_err(|_| AppError::InvalidMap("a star needs three coordinates"))?;
+            stars.push(star);
+        }
+
+        let portal_count = count(&mut lines, "missing portal count")?;
+        let mut portals = vec![Vec::new(); star_count];
+        for _ in 0..portal_count {
+            let line = lines.next().ok_or(AppError::InvalidMap("missing portal"))?;
+            let ends: Vec<usize> = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| AppError::InvalidMap("invalid portal"))?;
+            let [from, to] = ends[..] else {
+                return Err(AppError::InvalidMap("a portal needs two stars"));
+            };
+            if from >= star_count || to >= star_count {
+                return Err(AppError::InvalidMap("portal to an unknown star"));
+            }
+            portals[from].push(to);
+            portals[to].push(from);
+        }
+        Ok(StarMap { stars, portals })
+    }
+
+    /// The path through the fewest portals from `from` to `to`, found
+    /// breadth-first.
+    fn shortest_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut previous = vec![None; self.stars.len()];
+        previous[from] = Some(from);
+        let mut queue = VecDeque::from([from]);
+        while let Some(star) = queue.pop_front() {
+            if star == to {
+                let mut path = vec![to];
+                let mut current = to;
+                while current != from {
+                    current = previous[current]?;
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &next in &self.portals[star] {
+                if previous[next].is_none() {
+                    previous[next] = Some(star);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+fn distance(a: &Star, b: &Star) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Portals used and distance travelled from the first star to the last.
+async fn rocket(input: String) -> Result<String, AppError> {
+    let map = StarMap::parse(&input)?;
+    let last = map
+        .stars
+        .len()
+        .checked_sub(1)
+        .ok_or(AppError::InvalidMap("no stars"))?;
+    let path = map.shortest_path(0, last).ok_or(AppError::NoPath)?;
+    // Folding from 0.0 rather than `sum`, whose empty total is -0.0 and
+    // would print a path of no hops as "-0.000".
+    let travelled = path
+        .windows(2)
+        .map(|hop| distance(&map.stars[hop[0]], &map.stars[hop[1]]))
+        .fold(0.0, |total, hop| total + hop);
+    Ok(format!("{} {travelled:.3}", path.len() - 1))
+}
+
+pub const DAY: Day = Day {
+    prefix: "/22",
+    title: "Unpaired integers and star paths",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new()
+        .post("/integers", integers)
+        .post("/rocket", rocket)
+}
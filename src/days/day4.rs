@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 
-use axum::{extract::Json, http::StatusCode, response::IntoResponse, routing::post, Router};
+use axum::{http::StatusCode, response::IntoResponse};
 use serde::{Deserialize, Serialize};
 
+use crate::extract::Json;
+use crate::registry::{Day, Routes};
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Reindeer {
     name: String,
@@ -67,8 +70,14 @@ async fn contest_winners(Json(reindeers): Json<Vec<Contestent>>) -> impl IntoRes
     (StatusCode::OK, serde_json::to_string(&winners).unwrap()).into_response()
 }
 
-pub fn router() -> Router {
-    Router::new()
-        .route("/strength", post(calc_strength))
-        .route("/contest", post(contest_winners))
+pub const DAY: Day = Day {
+    prefix: "/4",
+    title: "Reindeer strength contest",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new()
+        .post("/strength", calc_strength)
+        .post("/contest", contest_winners)
 }
@@ -1,30 +1,28 @@
 use axum::{
-    extract::{Json, Query},
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::post,
-    Router,
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::error::Problem;
+use crate::extract::{Json, Query};
+use crate::registry::{Day, Routes};
+
 #[allow(dead_code)]
 #[derive(Error, Debug)]
 enum AppError {
-    #[error("Out of range")]
+    #[error("Out of Range")]
     OutOfRange,
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::OutOfRange => (
-                StatusCode::RANGE_NOT_SATISFIABLE,
-                "Out of Range".to_string(),
-            ),
+        let (status, code) = match self {
+            AppError::OutOfRange => (StatusCode::RANGE_NOT_SATISFIABLE, "out_of_range"),
         };
 
-        (status, error_message).into_response()
+        Problem::new(status, code, self.to_string()).into_response()
     }
 }
 
@@ -42,8 +40,8 @@ async fn sub_slice_names(
 ) -> Result<impl IntoResponse, AppError> {
     // println!("{:?} {:?}", pagination, names);
     let start = pagination.offset;
-    let mut end = if pagination.limit.is_some() {
-        pagination.offset + pagination.limit.unwrap()
+    let mut end = if let Some(limit) = pagination.limit {
+        pagination.offset + limit
     } else {
         names.len()
     };
@@ -51,12 +49,12 @@ async fn sub_slice_names(
         end = names.len();
     }
 
-    let temp_vec: Vec<String> = names[start..end].iter().map(|s| s.clone()).collect();
+    let temp_vec: Vec<String> = names[start..end].to_vec();
 
-    if pagination.split.is_some() {
+    if let Some(split) = pagination.split {
         let temp_vec: Vec<Vec<String>> = names[start..end]
-            .chunks(pagination.split.unwrap())
-            .map(|s| s.iter().map(|st| st.clone()).collect::<Vec<String>>())
+            .chunks(split)
+            .map(|s| s.to_vec())
             .collect();
 
         return Ok(serde_json::to_string(&temp_vec).unwrap());
@@ -65,6 +63,12 @@ async fn sub_slice_names(
     Ok(serde_json::to_string(&temp_vec).unwrap())
 }
 
-pub fn router() -> Router {
-    Router::new().route("/", post(sub_slice_names))
+pub const DAY: Day = Day {
+    prefix: "/5",
+    title: "Paginated names",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new().post("/", sub_slice_names)
 }
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use axum::{response::IntoResponse, routing::post, Router};
+use axum::response::IntoResponse;
+
+use crate::registry::{Day, Routes};
 
 async fn shelf_elf(body: String) -> impl IntoResponse {
     let elf_index: Vec<(usize, &str)> = body.match_indices("elf").collect();
@@ -29,6 +31,12 @@ async fn shelf_elf(body: String) -> impl IntoResponse {
     serde_json::to_string(&map).unwrap()
 }
 
-pub fn router() -> Router {
-    Router::new().route("/", post(shelf_elf))
+pub const DAY: Day = Day {
+    prefix: "/6",
+    title: "Elf on a shelf",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new().post("/", shelf_elf)
 }
@@ -3,30 +3,31 @@ use std::collections::HashMap;
 use axum::{
     http::{ HeaderMap, StatusCode },
     response::{ IntoResponse, Response },
-    routing::get,
-    Json,
-    Router,
 };
 use serde::{ Deserialize, Serialize };
 use thiserror::Error;
 use tower_cookies::{ CookieManagerLayer, Cookies };
 use base64::{ engine::general_purpose::STANDARD, Engine as _ };
 
+use crate::error::Problem;
+use crate::extract::Json;
+use crate::registry::{ Day, Routes };
+
 #[derive(Error, Debug)]
 enum AppError {
-    #[error("Missing Recipe.")]
+    #[error("Missing Recipe")]
     MissingRecipe,
-    #[error("Decode Error.")] DecodeError(base64::DecodeError),
+    #[error("Decode error {0}")] DecodeError(base64::DecodeError),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::MissingRecipe => (StatusCode::BAD_REQUEST, "Missing Recipe".to_string()),
-            AppError::DecodeError(e) => (StatusCode::BAD_REQUEST, format!("Decode error {e}")),
+        let (status, code) = match self {
+            AppError::MissingRecipe => (StatusCode::BAD_REQUEST, "missing_recipe"),
+            AppError::DecodeError(_) => (StatusCode::BAD_REQUEST, "invalid_base64"),
         };
 
-        (status, error_message).into_response()
+        Problem::new(status, code, self.to_string()).into_response()
     }
 }
 
@@ -145,9 +146,15 @@ async fn bake_recipe(cookie: Cookies, headers: HeaderMap) -> Result<Json<BakedCo
     Ok(Json(baked_cookies))
 }
 
-pub fn router() -> Router {
-    Router::new()
-        .route("/decode", get(decode_recipe))
-        .route("/bake", get(bake_recipe))
+pub const DAY: Day = Day {
+    prefix: "/7",
+    title: "Cookie recipes",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new()
+        .get("/decode", decode_recipe)
+        .get("/bake", bake_recipe)
         .layer(CookieManagerLayer::new())
 }
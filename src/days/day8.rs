@@ -1,28 +1,28 @@
 use num::Float;
 
 use axum::{
-    extract::Path,
     http::StatusCode,
     response::{ IntoResponse, Response },
-    routing::get,
-    Router,
 };
 use thiserror::Error;
 
+use crate::error::Problem;
+use crate::extract::Path;
+use crate::registry::{ Day, Routes };
+
 #[allow(dead_code)]
 #[derive(Error, Debug)]
 enum AppError {
-    #[error("incorrect pokedex id")] IncorrectPokedexID(rustemon::error::Error),
+    #[error("Incorrect request to PokeAPI: {0}")] IncorrectPokedexID(rustemon::error::Error),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::IncorrectPokedexID(e) =>
-                (StatusCode::BAD_REQUEST, format!("Incorrect request to PokeAPI: {e}")),
+        let (status, code) = match self {
+            AppError::IncorrectPokedexID(_) => (StatusCode::BAD_REQUEST, "incorrect_pokedex_id"),
         };
 
-        (status, error_message).into_response()
+        Problem::new(status, code, self.to_string()).into_response()
     }
 }
 
@@ -55,8 +55,14 @@ async fn leave_dent(Path(pokedex): Path<i64>) -> Result<impl IntoResponse, AppEr
     Ok(format!("{}", force))
 }
 
-pub fn router() -> Router {
-    Router::new()
-        .route("/weight/:pokedex", get(get_weight))
-        .route("/drop/:pokedex", get(leave_dent))
+pub const DAY: Day = Day {
+    prefix: "/8",
+    title: "PokéPhysics",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new()
+        .get("/weight/:pokedex", get_weight)
+        .get("/drop/:pokedex", leave_dent)
 }
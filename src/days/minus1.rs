@@ -1,9 +1,155 @@
-use axum::{ http::StatusCode, response::IntoResponse, routing::get, Router };
+use std::{ io, time::Duration };
 
-async fn fake_error() -> impl IntoResponse {
-    StatusCode::INTERNAL_SERVER_ERROR
+use axum::{
+    body::{ Body, Bytes },
+    http::{ header, StatusCode },
+    response::{ IntoResponse, Response },
+};
+use futures_util::stream;
+use rand::Rng;
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::time::sleep;
+
+use crate::error::Problem;
+use crate::extract::Query;
+use crate::registry::{ Day, Routes };
+
+/// Upper bound on `delay` plus the total time spent dripping a body.
+const MAX_DELAY_MS: u64 = 60_000;
+/// Upper bound on a generated response body.
+const MAX_SIZE: usize = 16 * 1024 * 1024;
+/// Byte used to fill generated bodies.
+const FILL: u8 = b'x';
+
+#[derive(Error, Debug)]
+enum AppError {
+    #[error("Invalid status code {0}")] InvalidStatus(u16),
+    #[error("Failure rate {0} must be between 0 and 1")] InvalidRate(f64),
+    #[error("Body size {0} exceeds the limit of {MAX_SIZE} bytes")] TooLarge(usize),
+    #[error("Delays add up to more than {MAX_DELAY_MS}ms")]
+    TooSlow,
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let code = match self {
+            AppError::InvalidStatus(_) => "invalid_status",
+            AppError::InvalidRate(_) => "invalid_rate",
+            AppError::TooLarge(_) => "body_too_large",
+            AppError::TooSlow => "too_slow",
+        };
+
+        Problem::new(StatusCode::BAD_REQUEST, code, self.to_string()).into_response()
+    }
+}
+
+fn default_chunk() -> usize {
+    1
+}
+
+/// What to do to a request, httpbin style. With no parameters the answer is
+/// a bare 500, as it has always been.
+#[derive(Debug, Deserialize)]
+struct Fault {
+    /// Status of a failed request; defaults to 500. Must be 200–599, since
+    /// hyper won't send a 1xx as the final response.
+    status: Option<u16>,
+    /// Probability in `[0, 1]` that the request fails; otherwise it gets a 200.
+    rate: Option<f64>,
+    /// Milliseconds to wait before the response headers are sent.
+    #[serde(default)]
+    delay: u64,
+    /// Bytes of body to send.
+    #[serde(default)]
+    size: usize,
+    /// Send the body `chunk` bytes at a time, waiting this many milliseconds
+    /// before each one.
+    drip: Option<u64>,
+    #[serde(default = "default_chunk")]
+    chunk: usize,
+    /// Drop the connection instead of finishing the response. Bytes already
+    /// dripped reach the client first; otherwise it usually sees nothing.
+    /// Only applies to failed requests.
+    #[serde(default)]
+    reset: bool,
 }
 
-pub fn router() -> Router {
-    Router::new().route("/error", get(fake_error))
+impl Fault {
+    fn validate(&self) -> Result<(), AppError> {
+        if let Some(status) = self.status {
+            if !(200..=599).contains(&status) {
+                return Err(AppError::InvalidStatus(status));
+            }
+        }
+        if let Some(rate) = self.rate {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(AppError::InvalidRate(rate));
+            }
+        }
+        if self.size > MAX_SIZE {
+            return Err(AppError::TooLarge(self.size));
+        }
+        let drip_total = self.drip.map_or(Some(0), |drip| {
+            drip.checked_mul(self.size.div_ceil(self.chunk.max(1)) as u64)
+        });
+        match drip_total.and_then(|drip| drip.checked_add(self.delay)) {
+            Some(total) if total <= MAX_DELAY_MS => Ok(()),
+            _ => Err(AppError::TooSlow),
+        }
+    }
+
+    fn body(&self, reset: bool) -> Body {
+        if self.drip.is_none() && !reset {
+            return Body::from(vec![FILL; self.size]);
+        }
+        let interval = self.drip.map(Duration::from_millis);
+        let chunk = if interval.is_some() { self.chunk.max(1) } else { self.size.max(1) };
+        let chunks = stream::unfold((self.size, reset), move |(remaining, reset)| async move {
+            if remaining > 0 {
+                if let Some(interval) = interval {
+                    sleep(interval).await;
+                }
+                let len = remaining.min(chunk);
+                Some((Ok(Bytes::from(vec![FILL; len])), (remaining - len, reset)))
+            } else if reset {
+                // Failing the body makes hyper abort the connection mid-response.
+                let error = io::Error::new(io::ErrorKind::ConnectionReset, "injected reset");
+                Some((Err(error), (0, false)))
+            } else {
+                None
+            }
+        });
+        Body::from_stream(chunks)
+    }
+}
+
+async fn fake_error(Query(fault): Query<Fault>) -> Result<Response, AppError> {
+    fault.validate()?;
+    let failed = rand::thread_rng().gen_bool(fault.rate.unwrap_or(1.0));
+    let status = match fault.status {
+        _ if !failed => StatusCode::OK,
+        Some(status) => StatusCode::from_u16(status).map_err(|_| AppError::InvalidStatus(status))?,
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
+    if fault.delay > 0 {
+        sleep(Duration::from_millis(fault.delay)).await;
+    }
+
+    let reset = failed && fault.reset;
+    if fault.size == 0 && !reset {
+        return Ok(status.into_response());
+    }
+    Ok((status, [(header::CONTENT_TYPE, "application/octet-stream")], fault.body(reset)).into_response())
+}
+
+pub const DAY: Day = Day {
+    prefix: "/-1",
+    title: "Fault injection",
+    router,
+};
+
+pub fn router() -> Routes {
+    Routes::new().get("/error", fake_error)
 }
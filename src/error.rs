@@ -0,0 +1,144 @@
+//! RFC 9457 problem details, the error body every day responds with.
+
+use axum::{
+    body::to_bytes,
+    extract::Request,
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use ulid::Ulid;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+pub const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+/// Longest incoming request ID that is passed through rather than replaced.
+const MAX_REQUEST_ID: usize = 128;
+/// Longest plain-text error body kept as a problem's detail.
+const MAX_DETAIL: usize = 4096;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// An `application/problem+json` body.
+///
+/// `type` is always `about:blank`, so `title` is the status's reason phrase
+/// and `code` is what clients should match on.
+#[derive(Debug, Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    code: &'static str,
+    request_id: String,
+    #[serde(flatten)]
+    extensions: Map<String, Value>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, code: &'static str, detail: impl Into<String>) -> Self {
+        Problem {
+            kind: "about:blank",
+            title: status.canonical_reason().unwrap_or("Unknown Status"),
+            status: status.as_u16(),
+            detail: detail.into(),
+            code,
+            request_id: current_request_id(),
+            extensions: Map::new(),
+        }
+    }
+
+    /// Adds an extension member alongside the standard ones.
+    pub fn with(mut self, name: &str, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        self.extensions.insert(name.to_string(), value);
+        self
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = (status, Json(self)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
+
+/// The ID of the request being handled, or a fresh one outside
+/// [`request_id`] (e.g. when a day's router is used on its own).
+pub fn current_request_id() -> String {
+    CURRENT_REQUEST_ID
+        .try_with(String::clone)
+        .unwrap_or_else(|_| Ulid::new().to_string())
+}
+
+fn incoming_request_id(request: &Request) -> Option<String> {
+    let id = request.headers().get(&REQUEST_ID)?.to_str().ok()?;
+    let valid = !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID
+        && id.bytes().all(|byte| byte.is_ascii_graphic());
+    valid.then(|| id.to_string())
+}
+
+/// Middleware that gives every request an ID, keeping the caller's
+/// `x-request-id` if it sent a sane one, and echoes it on the response.
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let id = incoming_request_id(&request).unwrap_or_else(|| Ulid::new().to_string());
+    let value = HeaderValue::from_str(&id).expect("request IDs are visible ASCII");
+    request.headers_mut().insert(REQUEST_ID, value.clone());
+    let mut response = CURRENT_REQUEST_ID.scope(id, next.run(request)).await;
+    response.headers_mut().insert(REQUEST_ID, value);
+    response
+}
+
+/// Stable code for an error that axum or tower produced rather than a day.
+fn fallback_code(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+        StatusCode::UPGRADE_REQUIRED => "upgrade_required",
+        status if status.is_server_error() => "internal_error",
+        _ => "http_error",
+    }
+}
+
+/// Middleware that turns error responses with an empty or plain-text body,
+/// such as axum's extractor rejections and `ServeDir`'s 404s, into problem
+/// details. JSON bodies, including problem details, pass through untouched.
+pub async fn problem_details(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    let plain = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_none_or(|value| value.starts_with("text/plain"));
+    if !(status.is_client_error() || status.is_server_error()) || !plain {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let detail = match to_bytes(body, MAX_DETAIL).await {
+        Ok(bytes) if !bytes.is_empty() => String::from_utf8_lossy(&bytes).into_owned(),
+        _ => status.canonical_reason().unwrap_or("Unknown Status").to_string(),
+    };
+    let problem = Problem::new(status, fallback_code(status), detail).into_response();
+    // Keep headers such as `allow` on a 405, but not the old body's framing.
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.remove(header::TRANSFER_ENCODING);
+    let (problem_parts, body) = problem.into_parts();
+    parts.headers.extend(problem_parts.headers);
+    Response::from_parts(parts, body)
+}
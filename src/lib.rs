@@ -1,13 +1,41 @@
+pub mod config;
+pub mod error;
+pub mod extract;
+pub mod registry;
+pub mod security;
+
 pub mod days {
-    pub mod day1;
-    pub mod day11;
-    pub mod day12;
-    pub mod day14;
-    pub mod day15;
-    pub mod day4;
-    pub mod day5;
-    pub mod day6;
-    pub mod day7;
-    pub mod day8;
-    pub mod minus1;
+    use crate::registry::Day;
+
+    /// Declares each day's module and lists it in [`ALL`], so a day can't be
+    /// compiled in without being mounted.
+    macro_rules! days {
+        ($($day:ident),* $(,)?) => {
+            $(pub mod $day;)*
+
+            /// Every day the service mounts, in the order the index lists them.
+            pub const ALL: &[Day] = &[$($day::DAY),*];
+        };
+    }
+
+    days!(
+        minus1,
+        day1,
+        day4,
+        day5,
+        day6,
+        day7,
+        day8,
+        day11,
+        day12,
+        day13,
+        day14,
+        day15,
+        day16,
+        day18,
+        day19,
+        day20,
+        day21,
+        day22,
+    );
 }
@@ -1,26 +1,6 @@
-use axum::{routing::get, Router};
-use shuttlings_cch23::days::{
-    day1, day11, day12, day14, day15, day4, day5, day6, day7, day8, minus1,
-};
-async fn hello_world() -> &'static str {
-    "Hello, bird!"
-}
+use shuttlings_cch23::{days, registry};
 
 #[shuttle_runtime::main]
 async fn main() -> shuttle_axum::ShuttleAxum {
-    let router = Router::new()
-        .route("/", get(hello_world))
-        .nest("/-1", minus1::router())
-        .nest("/1", day1::router())
-        .nest("/5", day5::router())
-        .nest("/6", day6::router())
-        .nest("/7", day7::router())
-        .nest("/8", day8::router())
-        .nest("/11", day11::router())
-        .nest("/12", day12::router())
-        .nest("/14", day14::router())
-        .nest("/15", day15::router())
-        .nest("/4", day4::router());
-
-    Ok(router.into())
+    Ok(registry::router(days::ALL).into())
 }
@@ -0,0 +1,294 @@
+use std::{convert::Infallible, fmt::Write, sync::Arc};
+
+use axum::{
+    extract::{Request, State},
+    handler::Handler,
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+    middleware,
+    response::{Html, IntoResponse, Response},
+    routing::{self, get, MethodRouter, Route},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use tower::{Layer, Service};
+
+use crate::error::{self, Problem};
+use crate::extract::{Json, Query};
+use crate::security::{security_headers, SecurityHeaders};
+
+/// One route a day mounts, relative to the day's prefix.
+#[derive(Debug, Clone, Serialize)]
+pub struct Endpoint {
+    pub methods: Vec<&'static str>,
+    pub path: String,
+}
+
+/// A [`Router`] that records every endpoint it mounts, so the catalog lists
+/// exactly what is routed.
+pub struct Routes<S = ()> {
+    router: Router<S>,
+    endpoints: Vec<Endpoint>,
+}
+
+impl<S> Default for Routes<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Routes {
+            router: Router::new(),
+            endpoints: Vec::new(),
+        }
+    }
+}
+
+impl<S> Routes<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Routes::default()
+    }
+
+    pub fn get<H, T>(self, path: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route("GET", path, routing::get(handler))
+    }
+
+    pub fn post<H, T>(self, path: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route("POST", path, routing::post(handler))
+    }
+
+    pub fn put<H, T>(self, path: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route("PUT", path, routing::put(handler))
+    }
+
+    pub fn delete<H, T>(self, path: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route("DELETE", path, routing::delete(handler))
+    }
+
+    /// Methods on a path already mounted are merged into its endpoint, as
+    /// axum merges them into its route.
+    fn route(mut self, method: &'static str, path: &str, method_router: MethodRouter<S>) -> Self {
+        self.router = self.router.route(path, method_router);
+        match self
+            .endpoints
+            .iter_mut()
+            .find(|endpoint| endpoint.path == path)
+        {
+            Some(endpoint) => endpoint.methods.push(method),
+            None => self.endpoints.push(Endpoint {
+                methods: vec![method],
+                path: path.to_string(),
+            }),
+        }
+        self
+    }
+
+    /// Mounts a service, such as a static file server, for everything below
+    /// `path`; it is listed as a `GET` of `path/*path`.
+    pub fn nest_service<T>(mut self, path: &str, service: T) -> Self
+    where
+        T: Service<Request, Error = Infallible> + Clone + Send + 'static,
+        T::Response: IntoResponse,
+        T::Future: Send + 'static,
+    {
+        self.router = self.router.nest_service(path, service);
+        self.endpoints.push(Endpoint {
+            methods: vec!["GET"],
+            path: format!("{path}/*path"),
+        });
+        self
+    }
+
+    pub fn merge(mut self, other: Routes<S>) -> Self {
+        self.router = self.router.merge(other.router);
+        self.endpoints.extend(other.endpoints);
+        self
+    }
+
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.router = self.router.layer(layer);
+        self
+    }
+
+    pub fn with_state<S2>(self, state: S) -> Routes<S2> {
+        Routes {
+            router: self.router.with_state(state),
+            endpoints: self.endpoints,
+        }
+    }
+
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+}
+
+impl Routes {
+    pub fn into_router(self) -> Router {
+        self.router
+    }
+}
+
+impl From<Routes> for Router {
+    fn from(routes: Routes) -> Self {
+        routes.into_router()
+    }
+}
+
+/// What a day module exposes so it can be mounted and listed in the index.
+pub struct Day {
+    pub prefix: &'static str,
+    pub title: &'static str,
+    pub router: fn() -> Routes,
+}
+
+impl Day {
+    /// Where an endpoint ends up once the day is nested under its prefix.
+    pub fn url(&self, endpoint: &Endpoint) -> String {
+        match endpoint.path.as_str() {
+            "/" => self.prefix.to_string(),
+            path => format!("{}{path}", self.prefix),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CatalogEndpoint {
+    methods: Vec<&'static str>,
+    path: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CatalogDay {
+    prefix: &'static str,
+    title: &'static str,
+    endpoints: Vec<CatalogEndpoint>,
+}
+
+fn catalog_day(day: &Day, endpoints: &[Endpoint]) -> CatalogDay {
+    CatalogDay {
+        prefix: day.prefix,
+        title: day.title,
+        endpoints: endpoints
+            .iter()
+            .map(|endpoint| CatalogEndpoint {
+                methods: endpoint.methods.clone(),
+                path: day.url(endpoint),
+            })
+            .collect(),
+    }
+}
+
+fn render_html(catalog: &[CatalogDay]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head><meta charset=\"utf-8\"><title>Routes</title></head>\n<body>\n",
+    );
+    for day in catalog {
+        let title = html_escape::encode_text(day.title);
+        let prefix = html_escape::encode_text(day.prefix);
+        writeln!(html, "<h2><code>{prefix}</code> {title}</h2>\n<ul>").unwrap();
+        for endpoint in &day.endpoints {
+            let methods = endpoint.methods.join(", ");
+            let path = html_escape::encode_text(&endpoint.path);
+            writeln!(html, "<li><code>{methods} {path}</code></li>").unwrap();
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Format {
+    Json,
+    Html,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexQuery {
+    format: Option<Format>,
+}
+
+/// The catalog as JSON, or as HTML for `?format=html` and for browsers.
+async fn index(
+    State(catalog): State<Arc<Vec<CatalogDay>>>,
+    Query(query): Query<IndexQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let wants_html = || {
+        headers
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .is_some_and(|accept| accept.contains("text/html"))
+    };
+    match query.format {
+        Some(Format::Html) => Html(render_html(&catalog)).into_response(),
+        None if wants_html() => Html(render_html(&catalog)).into_response(),
+        _ => Json(&*catalog).into_response(),
+    }
+}
+
+async fn not_found(uri: Uri) -> Problem {
+    Problem::new(
+        StatusCode::NOT_FOUND,
+        "no_route",
+        format!("No route for {}", uri.path()),
+    )
+}
+
+async fn method_not_allowed(method: Method, uri: Uri) -> Problem {
+    Problem::new(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "method_not_allowed",
+        format!("{method} is not allowed on {}", uri.path()),
+    )
+}
+
+/// Mounts every day under its prefix, with a catalog of what each one
+/// routed at `/`, tags every request with an ID and answers every error
+/// with problem details.
+///
+/// The index gets the security headers, since it can be served as HTML.
+pub fn router(days: &[Day]) -> Router {
+    let mut router = Router::new();
+    let mut catalog = Vec::with_capacity(days.len());
+    for day in days {
+        let routes = (day.router)();
+        catalog.push(catalog_day(day, routes.endpoints()));
+        router = router.nest(day.prefix, routes.into_router());
+    }
+    let headers = Arc::new(SecurityHeaders::default());
+    let index = get(index)
+        .with_state(Arc::new(catalog))
+        .layer(middleware::from_fn_with_state(headers, security_headers));
+    router
+        .route("/", index)
+        .fallback(not_found)
+        .method_not_allowed_fallback(method_not_allowed)
+        .layer(middleware::from_fn(error::problem_details))
+        .layer(middleware::from_fn(error::request_id))
+}
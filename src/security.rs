@@ -0,0 +1,125 @@
+//! Security response headers: a Content-Security-Policy with a fresh nonce
+//! per request, plus the usual hardening headers.
+
+use std::{env, sync::Arc};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::RngCore;
+use thiserror::Error;
+
+use crate::config::env_flag;
+
+/// Environment variable overriding the Content-Security-Policy template.
+pub const CSP_ENV: &str = "DAY14_CSP";
+/// Environment variable that, when `1` or `true`, sends the policy as report-only.
+pub const CSP_REPORT_ONLY_ENV: &str = "DAY14_CSP_REPORT_ONLY";
+
+/// `{nonce}` is replaced with a fresh nonce on every request.
+const DEFAULT_CSP: &str =
+    "default-src 'none'; script-src 'nonce-{nonce}'; style-src 'self' 'nonce-{nonce}'; \
+     img-src 'self' data:; base-uri 'none'; form-action 'none'; frame-ancestors 'none'";
+
+#[derive(Error, Debug)]
+#[error("Content-Security-Policy {0:?} is not a valid header value")]
+pub struct InvalidCsp(String);
+
+/// Per-request nonce; templates allow an inline script with `nonce="{{ csp_nonce }}"`.
+#[derive(Debug, Clone)]
+pub struct CspNonce(pub String);
+
+impl CspNonce {
+    fn generate() -> Self {
+        let mut bytes = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        CspNonce(STANDARD.encode(bytes))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    csp: String,
+    report_only: bool,
+    extra: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        SecurityHeaders {
+            csp: DEFAULT_CSP.to_string(),
+            report_only: false,
+            extra: vec![
+                (
+                    header::X_CONTENT_TYPE_OPTIONS,
+                    HeaderValue::from_static("nosniff"),
+                ),
+                (header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+                (
+                    header::REFERRER_POLICY,
+                    HeaderValue::from_static("no-referrer"),
+                ),
+            ],
+        }
+    }
+}
+
+impl SecurityHeaders {
+    pub fn from_env() -> Result<Self, InvalidCsp> {
+        let mut headers = SecurityHeaders::default();
+        if let Ok(csp) = env::var(CSP_ENV) {
+            headers = headers.csp(csp)?;
+        }
+        let report_only = env_flag(CSP_REPORT_ONLY_ENV);
+        Ok(headers.report_only(report_only))
+    }
+
+    /// Checked up front with a sample nonce, so a bad policy fails at startup
+    /// rather than going missing from every response.
+    pub fn csp(mut self, csp: impl Into<String>) -> Result<Self, InvalidCsp> {
+        self.csp = csp.into();
+        match self.policy(&CspNonce::generate()) {
+            Some(_) => Ok(self),
+            None => Err(InvalidCsp(self.csp)),
+        }
+    }
+
+    pub fn report_only(mut self, report_only: bool) -> Self {
+        self.report_only = report_only;
+        self
+    }
+
+    fn policy(&self, nonce: &CspNonce) -> Option<HeaderValue> {
+        HeaderValue::from_str(&self.csp.replace("{nonce}", &nonce.0)).ok()
+    }
+}
+
+/// Middleware that hands each request a [`CspNonce`] and stamps the response
+/// with the matching policy and the other configured security headers.
+pub async fn security_headers(
+    State(config): State<Arc<SecurityHeaders>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let nonce = CspNonce::generate();
+    request.extensions_mut().insert(nonce.clone());
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    if let Some(policy) = config.policy(&nonce) {
+        let name = if config.report_only {
+            header::CONTENT_SECURITY_POLICY_REPORT_ONLY
+        } else {
+            header::CONTENT_SECURITY_POLICY
+        };
+        headers.insert(name, policy);
+    }
+    for (name, value) in &config.extra {
+        headers.insert(name.clone(), value.clone());
+    }
+    response
+}
@@ -0,0 +1,58 @@
+//! Request helpers shared by the integration tests. Each test binary uses
+//! only some of them.
+#![allow(dead_code)]
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, HeaderMap, Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use tower::ServiceExt;
+
+/// A request to `uri`, with `body`, if any, sent as JSON.
+pub fn request(method: &str, uri: &str, body: Option<Value>) -> Request<Body> {
+    let request = Request::builder().method(method).uri(uri);
+    match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap()
+}
+
+/// Status, headers and body of the router's response to `request`.
+pub async fn send(router: &Router, request: Request<Body>) -> (StatusCode, HeaderMap, String) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (
+        status,
+        headers,
+        String::from_utf8_lossy(&bytes).into_owned(),
+    )
+}
+
+/// Status and body of the response to [`request`]`(method, uri, body)`.
+pub async fn call(
+    router: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, String) {
+    let (status, _, body) = send(router, request(method, uri, body)).await;
+    (status, body)
+}
+
+/// Like [`call`], with the body parsed as JSON, or `Null` if it isn't.
+pub async fn call_json(
+    router: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let (status, body) = call(router, method, uri, body).await;
+    (status, serde_json::from_str(&body).unwrap_or(Value::Null))
+}
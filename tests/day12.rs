@@ -0,0 +1,309 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{ http::StatusCode, Router };
+use jiff::{ SignedDuration, Timestamp };
+use serde_json::{ json, Value };
+use shuttlings_cch23::days::day12::{
+    self,
+    clock::{ Clock, TestClock },
+    store::{ MemoryStore, PacketRecord, StoreConfig, StoreError, COMPACT_MIN_ENTRIES },
+};
+
+use common::call;
+
+fn frozen_at(at: &str) -> (Router, Arc<TestClock>) {
+    let clock = Arc::new(TestClock::new());
+    clock.freeze(Some(at.parse().unwrap()));
+    let router = day12::router_with(Arc::new(MemoryStore::default()), clock.clone()).into_router();
+    (router, clock)
+}
+
+#[tokio::test]
+async fn load_reports_elapsed_seconds_from_the_clock() {
+    let (router, clock) = frozen_at("2023-12-12T12:00:00Z");
+    let (status, body) = call(&router, "POST", "/save/packet20231212", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let record: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(record["packet"], "packet20231212");
+    assert_eq!(record["saved"], "2023-12-12T12:00:00Z");
+
+    clock.advance(SignedDuration::from_secs(2));
+    assert_eq!(call(&router, "GET", "/load/packet20231212", None).await.1, "2");
+
+    clock.advance(SignedDuration::from_secs(5));
+    assert_eq!(call(&router, "GET", "/load/packet20231212", None).await.1, "7");
+
+    call(&router, "POST", "/save/packet20231212", None).await;
+    assert_eq!(call(&router, "GET", "/load/packet20231212", None).await.1, "0");
+}
+
+#[tokio::test]
+async fn load_supports_units_and_json() {
+    let (router, clock) = frozen_at("2023-12-12T12:00:00Z");
+    call(&router, "POST", "/save/timer", None).await;
+    clock.advance(SignedDuration::from_millis(61_250));
+
+    assert_eq!(call(&router, "GET", "/load/timer?unit=ms", None).await.1, "61250");
+    assert_eq!(call(&router, "GET", "/load/timer?unit=s", None).await.1, "61");
+    assert_eq!(call(&router, "GET", "/load/timer?unit=human", None).await.1, "1m 1s 250ms");
+
+    let (status, body) = call(&router, "GET", "/load/timer?unit=ms&format=json", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let loaded: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        loaded,
+        json!({
+            "packet": "timer",
+            "saved": "2023-12-12T12:00:00Z",
+            "now": "2023-12-12T12:01:01.25Z",
+            "duration": "PT1m1.25s",
+            "elapsed": 61250,
+        })
+    );
+}
+
+#[tokio::test]
+async fn packets_expire_after_their_ttl() {
+    let (router, clock) = frozen_at("2023-12-12T12:00:00Z");
+    let (_, body) = call(&router, "POST", "/save/short?ttl=10", None).await;
+    let record: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(record["expires"], "2023-12-12T12:00:10Z");
+    call(&router, "POST", "/save/forever", None).await;
+
+    clock.advance(SignedDuration::from_secs(9));
+    assert_eq!(call(&router, "GET", "/load/short", None).await.0, StatusCode::OK);
+
+    clock.advance(SignedDuration::from_secs(1));
+    assert_eq!(call(&router, "GET", "/load/short", None).await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(call(&router, "GET", "/load/forever", None).await.1, "10");
+}
+
+#[tokio::test]
+async fn packet_routes_hide_expired_packets() {
+    let (router, clock) = frozen_at("2023-12-12T12:00:00Z");
+    call(&router, "POST", "/save/short?ttl=10", None).await;
+    call(&router, "POST", "/save/forever", None).await;
+    clock.advance(SignedDuration::from_secs(10));
+
+    let (_, body) = call(&router, "GET", "/packets", None).await;
+    let page: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["packets"][0]["packet"], "forever");
+    assert_eq!(page["packets"].as_array().unwrap().len(), 1);
+
+    for method in ["GET", "DELETE"] {
+        assert_eq!(call(&router, method, "/packets/short", None).await.0, StatusCode::NOT_FOUND);
+        assert_eq!(call(&router, method, "/packets/missing", None).await.0, StatusCode::NOT_FOUND);
+    }
+    assert_eq!(call(&router, "DELETE", "/packets/forever", None).await.0, StatusCode::NO_CONTENT);
+    assert_eq!(call(&router, "GET", "/packets/forever", None).await.0, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn bulk_load_uses_one_instant_for_every_packet() {
+    let (router, clock) = frozen_at("2023-12-12T12:00:00Z");
+    call(&router, "POST", "/save", Some(json!([{ "packet": "a" }]))).await;
+    clock.advance(SignedDuration::from_secs(3));
+    call(&router, "POST", "/save", Some(json!([{ "packet": "b" }]))).await;
+    clock.advance(SignedDuration::from_secs(1));
+
+    let (_, body) = call(&router, "POST", "/load", Some(json!(["a", "b", "missing"]))).await;
+    let loaded: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        loaded,
+        json!([
+            { "packet": "a", "elapsed": 4 },
+            { "packet": "b", "elapsed": 1 },
+            { "packet": "missing", "elapsed": null },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn bulk_save_is_all_or_nothing() {
+    let (router, _) = frozen_at("2023-12-12T12:00:00Z");
+    let packets = json!([{ "packet": "a" }, { "packet": "b", "ttl": u64::MAX }]);
+    let (status, _) = call(&router, "POST", "/save", Some(packets)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(call(&router, "GET", "/load/a", None).await.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn future_count_follows_the_clock() {
+    let (router, clock) = frozen_at("2023-12-12T12:00:00Z");
+    // 2023-12-24T00:00:00Z and 2024-12-24T00:00:00Z, both Christmas Eve.
+    let ulids = json!(["01HJCHY50028T5CY4TQKFF04HM", "01JFTZD70028T5CY4TQKFF04HM"]);
+
+    let (status, body) = call(&router, "POST", "/ulids/5", Some(ulids.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    let counts: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(counts["in the future"], 2);
+    assert_eq!(counts["christmas eve"], 2);
+
+    clock.freeze(Some("2024-01-01T00:00:00Z".parse().unwrap()));
+    let counts: Value = serde_json::from_str(
+        &call(&router, "POST", "/ulids/5", Some(ulids)).await.1
+    ).unwrap();
+    assert_eq!(counts["in the future"], 1);
+}
+
+#[tokio::test]
+async fn generated_ulids_embed_the_clock_time() {
+    let (router, clock) = frozen_at("2023-12-12T12:00:00Z");
+    let (_, body) = call(&router, "GET", "/ulids/generate?count=3", None).await;
+    let ulids: Vec<String> = serde_json::from_str(&body).unwrap();
+    assert_eq!(ulids.len(), 3);
+    assert!(ulids.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let (_, body) = call(&router, "GET", &format!("/ulids/decode/{}", ulids[0]), None).await;
+    let decoded: Value = serde_json::from_str(&body).unwrap();
+    let expected: Timestamp = clock.now();
+    assert_eq!(decoded["timestamp"], expected.to_string());
+}
+
+#[tokio::test]
+async fn bad_ulids_are_reported_by_index() {
+    let (router, _) = frozen_at("2023-12-12T12:00:00Z");
+    let ulids = json!(["01HH9SJEG4V4V3ZPB4R8NG4CRC", "not a ulid"]);
+    let (status, body) = call(&router, "POST", "/ulids", Some(ulids)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let problem: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(problem["code"], "invalid_ulid");
+    assert_eq!(problem["index"], 1);
+
+    let (status, body) = call(&router, "GET", "/ulids/decode/nope", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let problem: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(problem["code"], "invalid_ulid");
+    assert!(problem.get("index").is_none());
+}
+
+#[tokio::test]
+async fn out_of_range_predicates_are_rejected() {
+    let (router, _) = frozen_at("2023-12-12T12:00:00Z");
+    let ulids = json!(["01HH9SJEG4V4V3ZPB4R8NG4CRC"]);
+    for predicate in [
+        json!({ "type": "weekday", "weekday": 9 }),
+        json!({ "type": "month_day", "month": 13, "day": 1 }),
+        json!({ "type": "hour_range", "start": 22, "end": 30 }),
+    ] {
+        let request = json!({ "ulids": ulids, "predicates": [predicate] });
+        let (status, body) = call(&router, "POST", "/ulids/analyze", Some(request)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{predicate}");
+        let problem: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(problem["code"], "invalid_predicate");
+    }
+
+    let (status, _) = call(&router, "POST", "/ulids/9", Some(ulids.clone())).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let predicate = json!({ "type": "hour_range", "start": 22, "end": 6, "name": "night" });
+    let request = json!({ "ulids": ulids, "predicates": [predicate] });
+    let (status, _) = call(&router, "POST", "/ulids/analyze", Some(request)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_clock_resumes_from_where_it_was_frozen() {
+    let clock = TestClock::new();
+    let at: Timestamp = "2023-12-12T12:00:00Z".parse().unwrap();
+    clock.freeze(Some(at));
+    clock.advance(SignedDuration::from_secs(60));
+    let resumed = clock.resume();
+    assert!(!clock.is_frozen());
+    assert!(resumed.duration_since(at) >= SignedDuration::from_secs(60));
+    assert!(resumed.duration_since(at) < SignedDuration::from_secs(61));
+}
+
+#[tokio::test]
+async fn file_store_survives_a_restart() {
+    let path = std::env::temp_dir().join(format!("day12-{}.log", ulid::Ulid::new()));
+    let config = StoreConfig::File(path.clone());
+    let clock = Arc::new(TestClock::new());
+    clock.freeze(Some("2023-12-12T12:00:00Z".parse().unwrap()));
+
+    let router = day12::router_with(config.open().unwrap(), clock.clone()).into_router();
+    call(&router, "POST", "/save/kept", None).await;
+    call(&router, "POST", "/save/dropped", None).await;
+    call(&router, "DELETE", "/packets/dropped", None).await;
+    drop(router);
+
+    clock.advance(SignedDuration::from_secs(30));
+    let router = day12::router_with(config.open().unwrap(), clock).into_router();
+    assert_eq!(call(&router, "GET", "/load/kept", None).await.1, "30");
+    assert_eq!(call(&router, "GET", "/load/dropped", None).await.0, StatusCode::BAD_REQUEST);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn eviction_lets_go_of_the_store_with_its_router() {
+    let store: Arc<MemoryStore> = Arc::new(MemoryStore::default());
+    let router = day12::router_with(store.clone(), Arc::new(TestClock::new())).into_router();
+    call(&router, "POST", "/save/packet", None).await;
+    drop(router);
+    assert_eq!(Arc::strong_count(&store), 1);
+}
+
+#[tokio::test]
+async fn file_store_compacts_its_log_on_open() {
+    let path = std::env::temp_dir().join(format!("day12-{}.log", ulid::Ulid::new()));
+    let config = StoreConfig::File(path.clone());
+    let clock = Arc::new(TestClock::new());
+
+    let router = day12::router_with(config.open().unwrap(), clock.clone()).into_router();
+    for _ in 0..3 {
+        call(&router, "POST", "/save/kept", None).await;
+    }
+    call(&router, "POST", "/save/dropped", None).await;
+    call(&router, "DELETE", "/packets/dropped", None).await;
+    drop(router);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 5);
+
+    let store = config.open().unwrap();
+    let log = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(log.lines().count(), 1);
+    assert!(store.load("kept").unwrap().is_some());
+    std::fs::remove_file(path).unwrap();
+}
+
+fn temp_log() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("day12-{}.log", ulid::Ulid::new()))
+}
+
+#[test]
+fn file_store_drops_a_torn_last_line() {
+    let saved = r#"{"op":"save","packet":"kept","saved":"2023-12-12T12:00:00Z"}"#;
+    for torn in [r#"{"op":"save","packet":"to"#.to_string(), "}{\n".to_string()] {
+        let path = temp_log();
+        std::fs::write(&path, format!("{saved}\n{torn}")).unwrap();
+        let store = StoreConfig::File(path.clone()).open().unwrap();
+        assert!(store.load("kept").unwrap().is_some());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), format!("{saved}\n"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn file_store_refuses_a_corrupt_log() {
+    let path = temp_log();
+    let saved = r#"{"op":"save","packet":"kept","saved":"2023-12-12T12:00:00Z"}"#;
+    std::fs::write(&path, format!("}}{{\n{saved}\n")).unwrap();
+    assert!(matches!(StoreConfig::File(path.clone()).open(), Err(StoreError::Corrupt(_))));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn file_store_compacts_its_log_while_running() {
+    let path = temp_log();
+    let store = StoreConfig::File(path.clone()).open().unwrap();
+    let now = Timestamp::now();
+    for _ in 0..2 * COMPACT_MIN_ENTRIES {
+        let record = PacketRecord { packet: "again".to_string(), saved: now, expires: None };
+        store.save(record).unwrap();
+    }
+    let entries = std::fs::read_to_string(&path).unwrap().lines().count();
+    assert!(entries < COMPACT_MIN_ENTRIES, "{entries} entries");
+    std::fs::remove_file(path).unwrap();
+}
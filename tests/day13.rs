@@ -0,0 +1,76 @@
+mod common;
+
+use axum::{http::StatusCode, Router};
+use serde_json::{json, Value};
+use shuttlings_cch23::days::day13;
+
+use common::call_json;
+
+async fn fresh() -> Router {
+    let router = day13::router_with(day13::connect("sqlite::memory:", &[]).unwrap()).into_router();
+    assert_eq!(
+        call_json(&router, "POST", "/reset", None).await.0,
+        StatusCode::OK
+    );
+    router
+}
+
+fn orders() -> Value {
+    json!([
+        { "id": 1, "region_id": 2, "gift_name": "Toy Train", "quantity": 5 },
+        { "id": 2, "region_id": 2, "gift_name": "Doll", "quantity": 8 },
+        { "id": 3, "region_id": 3, "gift_name": "Action Figure", "quantity": 12 },
+        { "id": 4, "region_id": 4, "gift_name": "Board Game", "quantity": 10 },
+        { "id": 5, "region_id": 2, "gift_name": "Teddy Bear", "quantity": 6 },
+        { "id": 6, "region_id": 3, "gift_name": "Toy Train", "quantity": 3 },
+    ])
+}
+
+#[tokio::test]
+async fn totals_and_popular_gift() {
+    let router = fresh().await;
+    assert_eq!(
+        call_json(&router, "GET", "/orders/total", None).await.1,
+        json!({ "total": 0 })
+    );
+    assert_eq!(
+        call_json(&router, "GET", "/orders/popular", None).await.1,
+        json!({ "popular": null })
+    );
+
+    let (status, _) = call_json(&router, "POST", "/orders", Some(orders())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        call_json(&router, "GET", "/orders/total", None).await.1,
+        json!({ "total": 44 })
+    );
+    assert_eq!(
+        call_json(&router, "GET", "/orders/popular", None).await.1,
+        json!({ "popular": "Action Figure" })
+    );
+
+    call_json(&router, "POST", "/reset", None).await;
+    assert_eq!(
+        call_json(&router, "GET", "/orders/total", None).await.1,
+        json!({ "total": 0 })
+    );
+}
+
+#[tokio::test]
+async fn duplicate_orders_conflict_without_leaking_the_driver_error() {
+    let router = fresh().await;
+    call_json(&router, "POST", "/orders", Some(orders())).await;
+    let batch = json!([
+        { "id": 7, "region_id": 1, "gift_name": "Kite", "quantity": 1 },
+        { "id": 1, "region_id": 1, "gift_name": "Kite", "quantity": 1 },
+    ]);
+    let (status, problem) = call_json(&router, "POST", "/orders", Some(batch)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(problem["code"], "duplicate_order");
+    assert!(!problem["detail"].as_str().unwrap().contains("UNIQUE"));
+    // The batch is one transaction, so order 7 wasn't kept either.
+    assert_eq!(
+        call_json(&router, "GET", "/orders/total", None).await.1,
+        json!({ "total": 44 })
+    );
+}
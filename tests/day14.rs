@@ -0,0 +1,142 @@
+mod common;
+
+use axum::{ body::Body, http::{ header, Request, StatusCode }, Router };
+use minijinja::context;
+use serde_json::json;
+use shuttlings_cch23::days::day14::{
+    self,
+    escape,
+    markdown,
+    templates::{ TemplateEngine, MAX_TEMPLATE_SIZE, MAX_UPLOADED_TEMPLATES },
+};
+use shuttlings_cch23::security::SecurityHeaders;
+
+use common::{ request, send };
+
+fn router(uploads: bool) -> Router {
+    let engine = TemplateEngine::new("templates/day14");
+    day14::router_with(engine, SecurityHeaders::default(), uploads).into_router()
+}
+
+async fn upload(router: &Router, name: &str, source: String) -> StatusCode {
+    let request = Request::put(format!("/templates/{name}")).body(Body::from(source)).unwrap();
+    send(router, request).await.0
+}
+
+#[tokio::test]
+async fn uploads_are_off_unless_enabled() {
+    let status = upload(&router(false), "mine.html", "hi".to_string()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(upload(&router(true), "mine.html", "hi".to_string()).await, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn uploads_cant_shadow_templates_on_disk() {
+    let router = router(true);
+    assert_eq!(upload(&router, "list.html", "owned".to_string()).await, StatusCode::CONFLICT);
+    assert_eq!(upload(&router, "page.html", "owned".to_string()).await, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn uploads_are_limited_in_size_and_number() {
+    let router = router(true);
+    let status = upload(&router, "big.html", "x".repeat(MAX_TEMPLATE_SIZE + 1)).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    for i in 0..MAX_UPLOADED_TEMPLATES {
+        assert_eq!(upload(&router, &format!("t{i}.html"), "hi".to_string()).await, StatusCode::CREATED);
+    }
+    let status = upload(&router, "one-more.html", "hi".to_string()).await;
+    assert_eq!(status, StatusCode::INSUFFICIENT_STORAGE);
+    // Replacing an upload doesn't count against the limit.
+    assert_eq!(upload(&router, "t0.html", "again".to_string()).await, StatusCode::CREATED);
+}
+
+#[test]
+fn url_escaper_only_keeps_safe_schemes() {
+    assert_eq!(escape::url("javascript:alert(1)"), "#");
+    assert_eq!(escape::url("  JavaScript:alert(1)"), "#");
+    assert_eq!(escape::url("data:text/html,<script>"), "#");
+    assert_eq!(escape::url("https://example.com/?q=1&r=2"), "https://example.com/?q=1&amp;r=2");
+    assert_eq!(escape::url("/path?q=\"x\""), "/path?q=%22x%22");
+}
+
+#[test]
+fn text_and_attribute_escapers_cover_their_contexts() {
+    assert_eq!(escape::text("<b>\"hi\"</b>"), "&lt;b&gt;\"hi\"&lt;/b&gt;");
+    assert_eq!(escape::attribute("\" onclick='x'"), "&quot; onclick=&#x27;x&#x27;");
+}
+
+#[test]
+fn js_filter_cant_close_the_script() {
+    let engine = TemplateEngine::new("templates/day14");
+    engine.add("js.html", "<script>let v = {{ value|js }};</script>".to_string()).unwrap();
+    let value = "</script><script>alert(1)</script>\u{2028}";
+    let html = engine.render("js.html", context! { value }).unwrap();
+    assert_eq!(html.matches("</script>").count(), 1);
+    assert!(html.contains("\\u003c/script\\u003e"));
+    assert!(html.contains("\\u2028"));
+}
+
+#[tokio::test]
+async fn sanitize_mode_strips_event_handlers_and_scripts() {
+    let content = r#"<b>bold</b><img src="x.png" onerror="alert(1)"><script>alert(2)</script>"#;
+    let sanitize = request("POST", "/safe?mode=sanitize", Some(json!({ "content": content })));
+    let (status, _, html) = send(&router(false), sanitize).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains("<b>bold</b>"));
+    assert!(!html.contains("onerror"));
+    assert!(!html.contains("alert"));
+}
+
+#[test]
+fn markdown_is_sanitized() {
+    let html = markdown::to_html(
+        "# Hi\n\n<script>alert(1)</script>\n\n[x](javascript:alert(2)) <img src=y onerror=alert(3)>\n\n```rust\nfn main() {}\n```"
+    );
+    assert!(html.contains("<h1>Hi</h1>"));
+    assert!(html.contains(r#"<code class="language-rust">"#));
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("javascript:"));
+    assert!(!html.contains("onerror"));
+}
+
+#[tokio::test]
+async fn csp_nonce_matches_the_rendered_page() {
+    let router = router(true);
+    let source = r#"<script nonce="{{ csp_nonce }}"></script>"#.to_string();
+    assert_eq!(upload(&router, "nonce.html", source).await, StatusCode::CREATED);
+
+    let mut nonces = Vec::new();
+    for _ in 0..2 {
+        let render = request("POST", "/render/nonce.html", Some(json!({})));
+        let (_, headers, html) = send(&router, render).await;
+        let nonce = html
+            .strip_prefix(r#"<script nonce=""#)
+            .and_then(|rest| rest.strip_suffix(r#""></script>"#))
+            .unwrap()
+            .to_string();
+        let policy = headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+        assert!(policy.contains(&format!("script-src 'nonce-{nonce}'")));
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        nonces.push(nonce);
+    }
+    assert_ne!(nonces[0], nonces[1]);
+}
+
+#[test]
+fn invalid_csp_is_refused() {
+    assert!(SecurityHeaders::default().csp("default-src 'self'\nscript-src *").is_err());
+    assert!(SecurityHeaders::default().csp("default-src 'self'; script-src 'nonce-{nonce}'").is_ok());
+}
+
+#[tokio::test]
+async fn csp_can_be_report_only() {
+    let headers = SecurityHeaders::default().report_only(true);
+    let engine = TemplateEngine::new("templates/day14");
+    let router = day14::router_with(engine, headers, false).into_router();
+    let body = json!({ "content": "hi" });
+    let (_, headers, _) = send(&router, request("POST", "/safe", Some(body))).await;
+    assert!(headers.contains_key(header::CONTENT_SECURITY_POLICY_REPORT_ONLY));
+    assert!(!headers.contains_key(header::CONTENT_SECURITY_POLICY));
+}
@@ -0,0 +1,226 @@
+mod common;
+
+use axum::{http::StatusCode, Router};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+use shuttlings_cch23::days::day15::{
+    self,
+    breach::{sha1_hex, BreachCorpus},
+    policy::{is_emoji, Policy, Rule},
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use common::{call_json, request, send};
+
+async fn post(router: &Router, uri: &str, input: &str) -> (StatusCode, Value) {
+    call_json(router, "POST", uri, Some(json!({ "input": input }))).await
+}
+
+/// The original quadratic nice/naughty check, kept as an oracle for ASCII input.
+fn reference_is_nice(input: &str) -> bool {
+    let doubled = input.chars().any(|c| {
+        let doubled_char = format!("{}{}", c, c);
+        input.contains(&doubled_char) && c.is_alphabetic()
+    });
+    let forbidden = ["ab", "cd", "pq", "xy"].iter().any(|ss| input.contains(ss));
+    let vowels = input.chars().filter(|c| "aeiouy".contains(*c)).count();
+    doubled && !forbidden && vowels >= 3
+}
+
+fn unicode_char() -> impl Strategy<Value = char> {
+    (0u32..=0x10ffff).prop_filter_map("not a scalar value", char::from_u32)
+}
+
+#[test]
+fn emoji_bounds_are_inclusive() {
+    for c in ['\u{1F600}', '\u{1F64F}', '\u{1F900}', '\u{1F9FF}'] {
+        assert!(is_emoji(c), "{:X} should count as an emoji", c as u32);
+    }
+    for c in ['\u{1F5FF}', '\u{1F650}', '\u{1F8FF}', '\u{1FA00}'] {
+        assert!(!is_emoji(c), "{:X} should not count as an emoji", c as u32);
+    }
+}
+
+#[test]
+fn game_range_includes_its_endpoints() {
+    let range = Rule::UnicodeRange {
+        start: 0x2980,
+        end: 0x2bff,
+    };
+    assert!(range.passes("\u{2980}"));
+    assert!(range.passes("\u{2BFF}"));
+    assert!(!range.passes("\u{297F}"));
+    assert!(!range.passes("\u{2C00}"));
+}
+
+#[test]
+fn combining_marks_do_not_count_towards_length() {
+    let decomposed = "a\u{0301}".repeat(7);
+    assert_eq!(decomposed.chars().count(), 14);
+    assert!(!Rule::MinLength { min: 8 }.passes(&decomposed));
+    assert!(Rule::MinLength { min: 7 }.passes(&decomposed));
+}
+
+proptest! {
+    #[test]
+    fn min_length_counts_graphemes(input in "\\PC*", min in 0usize..24) {
+        let expected = input.graphemes(true).count() >= min;
+        prop_assert_eq!(Rule::MinLength { min }.passes(&input), expected);
+    }
+
+    #[test]
+    fn unicode_range_contains_both_bounds(
+        start in unicode_char(),
+        end in unicode_char(),
+        filler in "[a-z]{0,8}",
+    ) {
+        let (start, end) = if start <= end { (start, end) } else { (end, start) };
+        let rule = Rule::UnicodeRange { start: start as u32, end: end as u32 };
+        let with_start = format!("{filler}{start}");
+        let with_end = format!("{end}{filler}");
+        prop_assert!(rule.passes(&with_start));
+        prop_assert!(rule.passes(&with_end));
+    }
+
+    #[test]
+    fn digit_sum_never_panics(input in "[0-9a-z]{0,400}") {
+        let _ = Rule::DigitSum { sum: 2023 }.passes(&input);
+    }
+
+    #[test]
+    fn first_failure_leads_the_full_report(input in any::<String>()) {
+        let policy = Policy::game();
+        let all = policy.evaluate_all(&input);
+        match policy.evaluate(&input) {
+            Ok(()) => prop_assert!(all.is_empty()),
+            Err(first) => prop_assert_eq!(Some(&first), all.first()),
+        }
+    }
+}
+
+#[test]
+fn nice_agrees_with_the_reference_on_ascii() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let router = day15::router().into_router();
+    proptest!(|(input in "[a-z]{0,16}")| {
+        let (status, body) = runtime.block_on(post(&router, "/nice", &input));
+        let expected = reference_is_nice(&input);
+        prop_assert_eq!(status == StatusCode::OK, expected);
+        prop_assert_eq!(&body["result"], if expected { "nice" } else { "naughty" });
+    });
+}
+
+#[test]
+fn routes_answer_any_unicode_input() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let router = day15::router().into_router();
+    proptest!(|(input in any::<String>())| {
+        for uri in ["/nice", "/nice?mode=all", "/game", "/game?mode=all"] {
+            let (status, body) = runtime.block_on(post(&router, uri, &input));
+            prop_assert!(!status.is_server_error());
+            prop_assert!(body["result"] == "nice" || body["result"] == "naughty");
+        }
+    });
+}
+
+#[tokio::test]
+async fn malformed_input_gets_a_json_error() {
+    let router = day15::router().into_router();
+    for uri in ["/nice", "/game", "/policy/game"] {
+        let malformed = request("POST", uri, Some(json!({ "pass": "x" })));
+        let (status, headers, body) = send(&router, malformed).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(headers["content-type"], "application/problem+json");
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["code"], "invalid_input");
+    }
+}
+
+#[test]
+fn digit_count_reason_is_descriptive_outside_the_game() {
+    let policy: Policy = serde_json::from_value(json!({
+        "rules": [{ "rule": "digit_count", "min": 3 }]
+    }))
+    .unwrap();
+    assert_eq!(
+        policy.evaluate("ab1").unwrap_err().reason,
+        "at least 3 digits"
+    );
+    assert_eq!(
+        Policy::game().evaluate("Ab3456xy").unwrap_err().reason,
+        "55555"
+    );
+}
+
+fn corpus_of(passwords: &[(&str, u64)]) -> BreachCorpus {
+    let mut lines: Vec<String> = passwords
+        .iter()
+        .map(|(password, count)| format!("{}:{count}\r\n", sha1_hex(password)))
+        .collect();
+    lines.sort();
+    let path = std::env::temp_dir().join(format!("day15-{}.txt", rand::random::<u64>()));
+    std::fs::write(&path, lines.concat()).unwrap();
+    let corpus = BreachCorpus::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    corpus
+}
+
+#[test]
+fn breach_corpus_finds_exact_hashes_only() {
+    let corpus = corpus_of(&[("password", 9_000_000), ("hunter2", 42), ("letmein", 7)]);
+    assert_eq!(
+        sha1_hex("password"),
+        "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"
+    );
+    assert_eq!(corpus.count("password"), 9_000_000);
+    assert_eq!(corpus.count("hunter2"), 42);
+    assert_eq!(corpus.count("letmein"), 7);
+    assert_eq!(corpus.count("correct horse battery staple"), 0);
+    assert_eq!(
+        corpus.range("5BAA6"),
+        [("1E4C9B93F3F0682250B6CF8331B7EE68FD8".to_string(), 9_000_000)]
+    );
+    assert!(corpus.range("00000").is_empty());
+    assert_eq!(corpus_of(&[]).count("password"), 0);
+}
+
+#[test]
+fn not_breached_rule_uses_the_corpus_when_loaded() {
+    let corpus = corpus_of(&[("hunter2", 42)]);
+    let rule = Rule::NotBreached { max_count: 0 };
+    assert!(!rule.passes("hunter3"));
+    assert!(!rule.check("hunter2", Some(&corpus)));
+    assert!(rule.check("hunter3", Some(&corpus)));
+    assert!(Rule::NotBreached { max_count: 42 }.check("hunter2", Some(&corpus)));
+}
+
+#[tokio::test]
+async fn strict_policy_fails_without_a_corpus() {
+    let router = day15::router().into_router();
+    let (status, body) = post(&router, "/policy/strict", "Correct-Horse-42").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["result"], "naughty");
+    assert_eq!(body["reason"], "no breach corpus to check against");
+}
+
+proptest! {
+    #[test]
+    fn breach_lookup_matches_a_linear_scan(
+        passwords in proptest::collection::btree_map("[a-z0-9]{1,6}", 1u64..1000, 0..64),
+        probe in "[a-z0-9]{1,6}",
+    ) {
+        let entries: Vec<(&str, u64)> =
+            passwords.iter().map(|(p, c)| (p.as_str(), *c)).collect();
+        let corpus = corpus_of(&entries);
+        for (password, count) in &passwords {
+            prop_assert_eq!(corpus.count(password), *count);
+        }
+        prop_assert_eq!(corpus.count(&probe), passwords.get(&probe).copied().unwrap_or(0));
+        let hash = sha1_hex(&probe);
+        let expected = passwords
+            .keys()
+            .filter(|p| sha1_hex(p).starts_with(&hash[..5]))
+            .count();
+        prop_assert_eq!(corpus.range(&hash[..5]).len(), expected);
+    }
+}
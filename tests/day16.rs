@@ -0,0 +1,73 @@
+mod common;
+
+use axum::{http::StatusCode, Router};
+use serde_json::{json, Value};
+use shuttlings_cch23::days::day16;
+
+use common::{call_json, request, send};
+
+fn router() -> Router {
+    day16::router().into_router()
+}
+
+#[tokio::test]
+async fn diff_then_patch_turns_from_into_to() {
+    let router = router();
+    let from = json!({ "elves": ["alvin", "bernard"], "sleigh": { "reindeer": 9 }, "snow": true });
+    let to = json!({ "elves": ["bernard", "chippy"], "sleigh": { "reindeer": 8 }, "cocoa": "hot" });
+
+    let diff = request("POST", "/diff", Some(json!({ "from": from, "to": to })));
+    let (status, headers, body) = send(&router, diff).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "application/json-patch+json");
+    let patch: Value = serde_json::from_str(&body).unwrap();
+    assert!(!patch.as_array().unwrap().is_empty());
+
+    let (status, patched) = call_json(
+        &router,
+        "POST",
+        "/patch",
+        Some(json!({ "document": from, "patch": patch })),
+    )
+    .await;
+    assert_eq!((status, patched), (StatusCode::OK, to));
+}
+
+#[tokio::test]
+async fn failed_patches_name_the_operation() {
+    let patch = json!([
+        { "op": "add", "path": "/a", "value": 1 },
+        { "op": "test", "path": "/a", "value": 2 },
+    ]);
+    let (status, problem) = call_json(
+        &router(),
+        "POST",
+        "/patch",
+        Some(json!({ "document": {}, "patch": patch })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(problem["code"], "patch_failed");
+    assert_eq!(problem["operation"], 1);
+    assert_eq!(problem["op"], "test");
+    assert_eq!(problem["path"], "/a");
+}
+
+#[tokio::test]
+async fn merge_null_removes_a_member() {
+    let document =
+        json!({ "gift": "train", "wrapping": "red", "tag": { "to": "kid", "from": "santa" } });
+    let patch = json!({ "wrapping": null, "tag": { "from": null }, "bow": true });
+    let (status, merged) = call_json(
+        &router(),
+        "POST",
+        "/merge",
+        Some(json!({ "document": document, "patch": patch })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        merged,
+        json!({ "gift": "train", "tag": { "to": "kid" }, "bow": true })
+    );
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use axum::{http::StatusCode, Router};
+use serde_json::json;
+use shuttlings_cch23::days::{day13, day18};
+
+use common::call_json;
+
+async fn seeded() -> Router {
+    let router = day18::router_with(day13::connect("sqlite::memory:", &[]).unwrap()).into_router();
+    assert_eq!(
+        call_json(&router, "POST", "/reset", None).await.0,
+        StatusCode::OK
+    );
+    let regions = json!([
+        { "id": 1, "name": "North Pole" },
+        { "id": 2, "name": "Europe" },
+        { "id": 3, "name": "North America" },
+        { "id": 4, "name": "South America" },
+        { "id": 5, "name": "Africa" },
+        { "id": 6, "name": "Asia" },
+        { "id": 7, "name": "Oceania" },
+    ]);
+    let orders = json!([
+        { "id": 1, "region_id": 2, "gift_name": "Board Game", "quantity": 5 },
+        { "id": 2, "region_id": 2, "gift_name": "Origami Set", "quantity": 8 },
+        { "id": 3, "region_id": 3, "gift_name": "Action Figure", "quantity": 12 },
+        { "id": 4, "region_id": 4, "gift_name": "Teddy Bear", "quantity": 10 },
+        { "id": 5, "region_id": 2, "gift_name": "Yarn Ball", "quantity": 6 },
+        { "id": 6, "region_id": 3, "gift_name": "Art Set", "quantity": 3 },
+        { "id": 7, "region_id": 5, "gift_name": "Robot Lego Kit", "quantity": 5 },
+        { "id": 8, "region_id": 6, "gift_name": "Drone", "quantity": 9 },
+    ]);
+    assert_eq!(
+        call_json(&router, "POST", "/regions", Some(regions))
+            .await
+            .0,
+        StatusCode::OK
+    );
+    assert_eq!(
+        call_json(&router, "POST", "/orders", Some(orders)).await.0,
+        StatusCode::OK
+    );
+    router
+}
+
+#[tokio::test]
+async fn region_totals_skip_regions_without_orders() {
+    let router = seeded().await;
+    assert_eq!(
+        call_json(&router, "GET", "/regions/total", None).await.1,
+        json!([
+            { "region": "Africa", "total": 5 },
+            { "region": "Asia", "total": 9 },
+            { "region": "Europe", "total": 19 },
+            { "region": "North America", "total": 15 },
+            { "region": "South America", "total": 10 },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn top_list_ranks_gifts_per_region() {
+    let router = seeded().await;
+    assert_eq!(
+        call_json(&router, "GET", "/regions/top_list/2", None)
+            .await
+            .1,
+        json!([
+            { "region": "Africa", "top_gifts": ["Robot Lego Kit"] },
+            { "region": "Asia", "top_gifts": ["Drone"] },
+            { "region": "Europe", "top_gifts": ["Origami Set", "Yarn Ball"] },
+            { "region": "North America", "top_gifts": ["Action Figure", "Art Set"] },
+            { "region": "North Pole", "top_gifts": [] },
+            { "region": "Oceania", "top_gifts": [] },
+            { "region": "South America", "top_gifts": ["Teddy Bear"] },
+        ])
+    );
+
+    let (status, lists) = call_json(&router, "GET", "/regions/top_list/0", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let lists = lists.as_array().unwrap();
+    assert_eq!(lists.len(), 7);
+    assert!(lists.iter().all(|list| list["top_gifts"] == json!([])));
+}
+
+#[tokio::test]
+async fn duplicate_regions_conflict() {
+    let router = seeded().await;
+    let (status, problem) = call_json(
+        &router,
+        "POST",
+        "/regions",
+        Some(json!([{ "id": 1, "name": "Again" }])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(problem["code"], "duplicate_id");
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::{http::StatusCode, Router};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use shuttlings_cch23::days::day19;
+use tokio::{net::TcpListener, net::TcpStream, time::timeout};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use common::call;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// How long to wait for a message that should arrive, or to be sure one won't.
+const WAIT: Duration = Duration::from_millis(200);
+
+/// Serves a fresh day19 router on an ephemeral port. The returned router
+/// shares its state, so `/views` and `/reset` can go through [`call`].
+async fn serve() -> (String, Router) {
+    let router = day19::router().into_router();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let app = router.clone();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("ws://{address}"), router)
+}
+
+async fn connect(base: &str, path: &str) -> Socket {
+    connect_async(format!("{base}{path}")).await.unwrap().0
+}
+
+async fn send(socket: &mut Socket, text: &str) {
+    socket.send(Message::Text(text.to_string())).await.unwrap();
+}
+
+/// The next text message, or `None` if nothing arrives in time.
+async fn recv(socket: &mut Socket) -> Option<String> {
+    loop {
+        match timeout(WAIT, socket.next()).await {
+            Ok(Some(Ok(Message::Text(text)))) => return Some(text),
+            Ok(Some(Ok(_))) => continue,
+            _ => return None,
+        }
+    }
+}
+
+async fn tweet(socket: &mut Socket, message: &str) {
+    send(socket, &json!({ "message": message }).to_string()).await;
+}
+
+async fn recv_tweet(socket: &mut Socket) -> Option<Value> {
+    recv(socket)
+        .await
+        .map(|text| serde_json::from_str(&text).unwrap())
+}
+
+/// Joins `room` as `user`, returning once the room has echoed a greeting
+/// back, so the server is known to be subscribed.
+async fn join(base: &str, room: u32, user: &str) -> Socket {
+    let mut socket = connect(base, &format!("/ws/room/{room}/user/{user}")).await;
+    tweet(&mut socket, "hello").await;
+    assert_eq!(
+        recv_tweet(&mut socket).await,
+        Some(json!({ "user": user, "message": "hello" }))
+    );
+    socket
+}
+
+/// Polls `/views` until it reaches `expected`, since a view is counted
+/// just after the message goes out.
+async fn assert_views(router: &Router, expected: u64) {
+    let mut views = 0;
+    for _ in 0..50 {
+        views = call(router, "GET", "/views", None).await.1.parse().unwrap();
+        if views == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(views, expected);
+}
+
+#[tokio::test]
+async fn ping_is_answered_only_after_serve() {
+    let (base, _) = serve().await;
+    let mut socket = connect(&base, "/ws/ping").await;
+
+    send(&mut socket, "ping").await;
+    assert_eq!(recv(&mut socket).await, None);
+
+    send(&mut socket, "serve").await;
+    send(&mut socket, "ping").await;
+    assert_eq!(recv(&mut socket).await.as_deref(), Some("pong"));
+    send(&mut socket, "pong").await;
+    assert_eq!(recv(&mut socket).await, None);
+}
+
+#[tokio::test]
+async fn tweets_reach_only_their_room() {
+    let (base, router) = serve().await;
+    let mut alice = join(&base, 1, "alice").await;
+    let mut bob = join(&base, 1, "bob").await;
+    let mut carol = join(&base, 2, "carol").await;
+    let hello = json!({ "user": "bob", "message": "hello" });
+    assert_eq!(recv_tweet(&mut alice).await, Some(hello));
+
+    tweet(&mut alice, "hi bob").await;
+    let expected = json!({ "user": "alice", "message": "hi bob" });
+    assert_eq!(recv_tweet(&mut alice).await, Some(expected.clone()));
+    assert_eq!(recv_tweet(&mut bob).await, Some(expected));
+    assert_eq!(recv_tweet(&mut carol).await, None);
+
+    // Greetings: alice's to herself, bob's to both, carol's to herself.
+    // Then alice's tweet to both.
+    assert_views(&router, 4 + 2).await;
+}
+
+#[tokio::test]
+async fn overlong_tweets_are_dropped() {
+    let (base, router) = serve().await;
+    let mut alice = join(&base, 7, "alice").await;
+
+    tweet(&mut alice, &"x".repeat(129)).await;
+    let longest = "é".repeat(128);
+    tweet(&mut alice, &longest).await;
+    assert_eq!(
+        recv_tweet(&mut alice).await,
+        Some(json!({ "user": "alice", "message": longest }))
+    );
+    assert_eq!(recv_tweet(&mut alice).await, None);
+    assert_views(&router, 2).await;
+}
+
+#[tokio::test]
+async fn reset_zeroes_the_views() {
+    let (base, router) = serve().await;
+    let mut alice = join(&base, 3, "alice").await;
+    tweet(&mut alice, "one").await;
+    recv_tweet(&mut alice).await.unwrap();
+    assert_views(&router, 2).await;
+
+    assert_eq!(
+        call(&router, "POST", "/reset", None).await.0,
+        StatusCode::OK
+    );
+    assert_views(&router, 0).await;
+
+    tweet(&mut alice, "two").await;
+    recv_tweet(&mut alice).await.unwrap();
+    assert_views(&router, 1).await;
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use shuttlings_cch23::days::day20;
+
+use common::send;
+
+/// A tarred git repository whose `christmas` branch has one commit per
+/// `(author, path, contents)`, oldest first, each holding only that file.
+fn repository(commits: &[(&str, &str, &str)]) -> Vec<u8> {
+    let dir = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir.path())
+            .stdin(Stdio::piped())
+            .spawn()
+            .unwrap()
+    };
+    assert!(git(&["init", "--quiet"]).wait().unwrap().success());
+
+    let mut import = git(&["fast-import", "--quiet"]);
+    let stream: String = commits
+        .iter()
+        .enumerate()
+        .map(|(time, (author, path, contents))| {
+            format!(
+                "commit refs/heads/christmas\n\
+                 author {author} <{author}@north.pole> {time} +0000\n\
+                 committer {author} <{author}@north.pole> {time} +0000\n\
+                 data 6\nfound\n\
+                 deleteall\n\
+                 M 100644 inline {path}\n\
+                 data {}\n{contents}\n\n",
+                contents.len()
+            )
+        })
+        .collect();
+    import
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stream.as_bytes())
+        .unwrap();
+    assert!(import.wait().unwrap().success());
+
+    let mut archive = tar::Builder::new(Vec::new());
+    archive
+        .append_dir_all(".git", dir.path().join(".git"))
+        .unwrap();
+    archive.into_inner().unwrap()
+}
+
+async fn cookie(archive: Vec<u8>) -> (StatusCode, String) {
+    let request = Request::post("/cookie").body(Body::from(archive)).unwrap();
+    let (status, _, body) = send(&day20::router().into_router(), request).await;
+    (status, body)
+}
+
+fn commit_hash(archive: &[u8], revision: &str) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    tar::Archive::new(archive).unpack(dir.path()).unwrap();
+    let output = Command::new("git")
+        .args(["rev-parse", revision])
+        .current_dir(Path::new(dir.path()))
+        .output()
+        .unwrap();
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[tokio::test]
+async fn finds_a_cookie() {
+    let archive = repository(&[("Santa", "santa.txt", "a COOKIE!")]);
+    let hash = commit_hash(&archive, "christmas");
+    assert_eq!(
+        cookie(archive).await,
+        (StatusCode::OK, format!("Santa {hash}"))
+    );
+
+    let (status, _) = cookie(repository(&[("Santa", "santa.txt", "no cookie")])).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn finds_a_cookie_in_deeply_nested_trees() {
+    let path = format!("{}santa.txt", "d/".repeat(5000));
+    let archive = repository(&[("Santa", &path, "COOKIE")]);
+    let hash = commit_hash(&archive, "christmas");
+    assert_eq!(
+        cookie(archive).await,
+        (StatusCode::OK, format!("Santa {hash}"))
+    );
+}
+
+#[tokio::test]
+async fn picks_the_newest_commit_with_a_cookie() {
+    let archive = repository(&[
+        ("Alice", "santa.txt", "an old COOKIE"),
+        ("Bob", "santa.txt", "a newer COOKIE"),
+        ("Carol", "santa.txt", "crumbs"),
+        ("Dave", "elf.txt", "COOKIE, but not for santa"),
+    ]);
+    let hash = commit_hash(&archive, "christmas~2");
+    assert_eq!(
+        cookie(archive).await,
+        (StatusCode::OK, format!("Bob {hash}"))
+    );
+}
+
+/// A tar archive of `files`, plus a directory entry that neither route counts.
+fn archive_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut archive = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Directory);
+    header.set_size(0);
+    header.set_mode(0o755);
+    archive
+        .append_data(&mut header, "presents/", std::io::empty())
+        .unwrap();
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        archive.append_data(&mut header, path, *contents).unwrap();
+    }
+    archive.into_inner().unwrap()
+}
+
+#[tokio::test]
+async fn counts_and_sizes_archive_files() {
+    let router = day20::router().into_router();
+    let archive = archive_of(&[
+        ("presents/train.txt", b"choo choo"),
+        ("presents/doll.txt", b"hello"),
+        ("empty.txt", b""),
+    ]);
+    for (uri, expected) in [("/archive_files", "3"), ("/archive_files_size", "14")] {
+        let request = Request::post(uri)
+            .body(Body::from(archive.clone()))
+            .unwrap();
+        let (status, _, body) = send(&router, request).await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, expected));
+    }
+
+    let request = Request::post("/archive_files")
+        .body(Body::from(
+            "not a tarball at all, but long enough to hold a header".repeat(20),
+        ))
+        .unwrap();
+    assert_eq!(send(&router, request).await.0, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use axum::http::StatusCode;
+use shuttlings_cch23::days::day21;
+
+use common::call;
+
+async fn get(uri: &str) -> (StatusCode, String) {
+    call(&day21::router().into_router(), "GET", uri, None).await
+}
+
+// Both cells' centres were checked against S2's own table-driven decoding.
+#[tokio::test]
+async fn decodes_cell_coordinates() {
+    let (status, body) =
+        get("/coords/0100111110010011000110011001010101011111000010100011110001011011").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "83°39'54.324''N 30°37'40.584''W");
+}
+
+#[tokio::test]
+async fn resolves_cells_with_the_bundled_countries() {
+    let (status, body) =
+        get("/country/0010000111110000011111100000111010111100000100111101111011000101").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "Madagascar");
+
+    let (status, body) =
+        get("/coords/0010000111110000011111100000111010111100000100111101111011000101").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "18°54'55.944''S 47°31'17.976''E");
+}
+
+#[tokio::test]
+async fn cells_outside_every_country_are_not_found() {
+    // Face 5's first leaf lies in the open South Pacific.
+    let (status, _) = get(&format!("/country/{:b}", (5u64 << 61) | 1)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
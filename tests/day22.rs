@@ -0,0 +1,98 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use serde_json::Value;
+use shuttlings_cch23::days::day22;
+
+use common::send;
+
+const SAMPLE_MAP: &str = "5
+0 1 0
+-2 2 3
+3 -3 -5
+1 1 5
+4 3 5
+4
+0 1
+2 4
+3 4
+1 2
+";
+
+async fn post(uri: &str, body: impl Into<Body>) -> (StatusCode, String) {
+    let request = Request::post(uri).body(body.into()).unwrap();
+    let (status, _, body) = send(&day22::router().into_router(), request).await;
+    (status, body)
+}
+
+/// The problem code of an error response.
+fn code(body: &str) -> String {
+    let problem: Value = serde_json::from_str(body).unwrap();
+    problem["code"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn integers_finds_the_unpaired_number() {
+    let numbers = "888\n77\n888\n22\n77\n";
+    assert_eq!(
+        post("/integers", numbers).await,
+        (StatusCode::OK, "🎁".repeat(22))
+    );
+
+    let unterminated = "5\n\n3\n  5  \n";
+    assert_eq!(
+        post("/integers", unterminated).await,
+        (StatusCode::OK, "🎁".repeat(3))
+    );
+
+    let (status, body) = post("/integers", "1\nmany\n").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(code(&body), "invalid_number");
+}
+
+#[tokio::test]
+async fn integers_enforces_its_limits() {
+    let long_line = format!("{}1\n", "0".repeat(64));
+    let (status, body) = post("/integers", long_line).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(code(&body), "invalid_number");
+
+    let most = 1u64 << 20;
+    let (status, body) = post("/integers", most.to_string()).await;
+    assert_eq!((status, body.chars().count()), (StatusCode::OK, 1 << 20));
+
+    let (status, body) = post("/integers", (most + 1).to_string()).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(code(&body), "too_many_presents");
+}
+
+#[tokio::test]
+async fn rocket_follows_the_fewest_portals() {
+    assert_eq!(
+        post("/rocket", SAMPLE_MAP).await,
+        (StatusCode::OK, "3 26.123".to_string())
+    );
+
+    let single = "1\n1 2 3\n0\n";
+    assert_eq!(
+        post("/rocket", single).await,
+        (StatusCode::OK, "0 0.000".to_string())
+    );
+}
+
+#[tokio::test]
+async fn rocket_reports_unreachable_and_malformed_maps() {
+    let disconnected = "3\n0 0 0\n1 0 0\n2 0 0\n1\n0 1\n";
+    let (status, body) = post("/rocket", disconnected).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(code(&body), "no_path");
+
+    for malformed in ["", "2\n0 0 0\n", "1\n0 0\n0\n", "2\n0 0 0\n1 1 1\n1\n0 2\n"] {
+        let (status, body) = post("/rocket", malformed).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{malformed:?}");
+        assert_eq!(code(&body), "invalid_star_map");
+    }
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+use shuttlings_cch23::{days, error::REQUEST_ID, registry};
+
+/// Status, request ID and problem body of an error response.
+async fn send_problem(
+    router: &Router,
+    request: Request<Body>,
+) -> (StatusCode, Option<String>, Value) {
+    let (status, headers, body) = common::send(router, request).await;
+    assert_eq!(headers[header::CONTENT_TYPE], "application/problem+json");
+    let id = headers
+        .get(REQUEST_ID)
+        .map(|id| id.to_str().unwrap().to_string());
+    (status, id, serde_json::from_str(&body).unwrap())
+}
+
+#[tokio::test]
+async fn day_errors_are_problem_details() {
+    let router = registry::router(days::ALL);
+    let request = Request::post("/22/integers")
+        .body(Body::from("12\nnope\n"))
+        .unwrap();
+    let (status, id, problem) = send_problem(&router, request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(problem["type"], "about:blank");
+    assert_eq!(problem["title"], "Bad Request");
+    assert_eq!(problem["status"], 400);
+    assert_eq!(problem["code"], "invalid_number");
+    assert_eq!(problem["detail"], "Invalid number \"nope\"");
+    assert_eq!(problem["request_id"], id.unwrap());
+}
+
+#[tokio::test]
+async fn request_ids_are_kept_only_when_sane() {
+    let router = registry::router(days::ALL);
+    let request = Request::get("/nowhere")
+        .header(REQUEST_ID, "trace-42")
+        .body(Body::empty())
+        .unwrap();
+    let (_, id, problem) = send_problem(&router, request).await;
+    assert_eq!(id.as_deref(), Some("trace-42"));
+    assert_eq!(problem["request_id"], "trace-42");
+
+    let request = Request::get("/nowhere")
+        .header(REQUEST_ID, "x".repeat(200))
+        .body(Body::empty())
+        .unwrap();
+    let (_, id, problem) = send_problem(&router, request).await;
+    let id = id.unwrap();
+    assert_ne!(id, "x".repeat(200));
+    assert_eq!(problem["request_id"], id);
+}
+
+#[tokio::test]
+async fn extension_members_sit_beside_the_standard_ones() {
+    let router = registry::router(days::ALL);
+    let body = json!({
+        "document": { "a": 1 },
+        "patch": [
+            { "op": "replace", "path": "/a", "value": 2 },
+            { "op": "remove", "path": "/b" },
+        ],
+    });
+    let request = Request::post("/16/patch")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let (status, _, problem) = send_problem(&router, request).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(problem["code"], "patch_failed");
+    assert_eq!(problem["operation"], 1);
+    assert_eq!(problem["op"], "remove");
+    assert_eq!(problem["path"], "/b");
+}
+
+#[tokio::test]
+async fn extractor_rejections_are_problem_details() {
+    let router = registry::router(days::ALL);
+    let request = Request::post("/12/ulids")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from("[\"01BRZ3NDEKTSV4RRFFQ69G5FAV\","))
+        .unwrap();
+    let (status, id, problem) = send_problem(&router, request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(problem["code"], "invalid_json");
+    assert_eq!(problem["request_id"], id.unwrap());
+
+    let request = Request::post("/16/patch")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({ "document": {}, "patch": "nope" }).to_string(),
+        ))
+        .unwrap();
+    let (status, _, problem) = send_problem(&router, request).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(problem["code"], "invalid_json");
+
+    let request = Request::get("/18/regions/top_list/abc")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _, problem) = send_problem(&router, request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(problem["code"], "invalid_path");
+
+    let request = Request::get("/12/packets?limit=lots")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _, problem) = send_problem(&router, request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(problem["code"], "invalid_query");
+}
+
+#[tokio::test]
+async fn framework_errors_are_problem_details() {
+    let router = registry::router(days::ALL);
+    let cases = [
+        (
+            Request::get("/19/ws/ping").body(Body::empty()).unwrap(),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            Request::post("/14/markdown")
+                .header(header::CONTENT_TYPE, "text/plain")
+                .body(Body::from(vec![0xff, 0xfe, 0xfd]))
+                .unwrap(),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            Request::post("/11/red_pixels")
+                .header(header::CONTENT_TYPE, "image/png")
+                .body(Body::from("not multipart"))
+                .unwrap(),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            Request::post("/20/archive_files")
+                .body(Body::from(vec![0u8; 64 * 1024 * 1024 + 1]))
+                .unwrap(),
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+        (
+            Request::get("/11/assets/nope.png")
+                .body(Body::empty())
+                .unwrap(),
+            StatusCode::NOT_FOUND,
+        ),
+    ];
+    for (request, expected) in cases {
+        let uri = request.uri().clone();
+        let (status, id, problem) = send_problem(&router, request).await;
+        assert_eq!(status, expected, "{uri}");
+        assert_eq!(problem["status"], expected.as_u16(), "{uri}");
+        assert!(problem["code"].is_string(), "{uri}");
+        assert_eq!(problem["request_id"], id.unwrap(), "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn wrong_methods_are_problem_details() {
+    let router = registry::router(days::ALL);
+    for (method, uri) in [("GET", "/12/save/x"), ("DELETE", "/")] {
+        let (status, headers, body) =
+            common::send(&router, common::request(method, uri, None)).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED, "{uri}");
+        assert_eq!(headers[header::CONTENT_TYPE], "application/problem+json");
+        let problem: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(problem["code"], "method_not_allowed", "{uri}");
+        if uri == "/12/save/x" {
+            assert_eq!(headers[header::ALLOW], "POST");
+        }
+    }
+}
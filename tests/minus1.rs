@@ -0,0 +1,74 @@
+mod common;
+
+use std::time::{ Duration, Instant };
+
+use axum::{ body::to_bytes, http::StatusCode };
+use serde_json::Value;
+use shuttlings_cch23::days::minus1;
+use tower::ServiceExt;
+
+use common::request;
+
+/// Unlike [`common::send`], keeps a failed body read, since dripping can time out.
+async fn get(uri: &str) -> (StatusCode, Result<Vec<u8>, axum::Error>) {
+    let response = minus1::router().into_router().oneshot(request("GET", uri, None)).await.unwrap();
+    let status = response.status();
+    (status, to_bytes(response.into_body(), usize::MAX).await.map(|bytes| bytes.to_vec()))
+}
+
+async fn problem_code(uri: &str) -> String {
+    let (status, body) = get(uri).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+    let problem: Value = serde_json::from_slice(&body.unwrap()).unwrap();
+    problem["code"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn bare_request_is_a_500() {
+    let (status, body) = get("/error").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(body.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn status_must_be_a_final_response() {
+    assert_eq!(get("/error?status=418").await.0, StatusCode::IM_A_TEAPOT);
+    assert_eq!(get("/error?status=200").await.0, StatusCode::OK);
+    for status in [100, 101, 199, 600] {
+        assert_eq!(problem_code(&format!("/error?status={status}")).await, "invalid_status");
+    }
+}
+
+#[tokio::test]
+async fn rate_decides_whether_the_request_fails() {
+    assert_eq!(get("/error?rate=0&status=503").await.0, StatusCode::OK);
+    assert_eq!(get("/error?rate=1&status=503").await.0, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(problem_code("/error?rate=1.5").await, "invalid_rate");
+    assert_eq!(problem_code("/error?rate=-0.1").await, "invalid_rate");
+}
+
+#[tokio::test]
+async fn size_sets_the_body_length() {
+    let (_, body) = get("/error?size=10").await;
+    assert_eq!(body.unwrap(), b"xxxxxxxxxx");
+    assert_eq!(problem_code(&format!("/error?size={}", 16 * 1024 * 1024 + 1)).await, "body_too_large");
+}
+
+#[tokio::test]
+async fn drip_sends_the_body_in_timed_chunks() {
+    let started = Instant::now();
+    let (status, body) = get("/error?size=5&chunk=2&drip=20").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body.unwrap(), b"xxxxx");
+    // Three chunks, each preceded by a 20ms wait.
+    assert!(started.elapsed() >= Duration::from_millis(60));
+
+    assert_eq!(problem_code("/error?size=100&drip=1000").await, "too_slow");
+}
+
+#[tokio::test]
+async fn reset_fails_the_body() {
+    let (_, body) = get("/error?size=3&reset=true").await;
+    assert!(body.is_err());
+    assert_eq!(get("/error?rate=0&size=3&reset=true").await.1.unwrap(), b"xxx");
+}
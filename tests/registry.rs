@@ -0,0 +1,108 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use serde_json::Value;
+use shuttlings_cch23::days::{
+    self,
+    day12::{self, clock::TestClock, store::MemoryStore},
+    day14::{self, templates::TemplateEngine},
+};
+use shuttlings_cch23::registry::{self, Routes};
+use shuttlings_cch23::security::SecurityHeaders;
+
+use common::{call, send};
+
+/// Fills every `:param` and `*wildcard` segment with a placeholder.
+fn concrete(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.chars().next() {
+            Some(':' | '*') => "x",
+            _ => segment,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[tokio::test]
+async fn every_listed_endpoint_is_routed() {
+    let router = registry::router(days::ALL);
+    let (_, body) = call(&router, "GET", "/", None).await;
+    let catalog: Value = serde_json::from_str(&body).unwrap();
+    for day in catalog.as_array().unwrap() {
+        for endpoint in day["endpoints"].as_array().unwrap() {
+            let uri = concrete(endpoint["path"].as_str().unwrap());
+            for method in endpoint["methods"].as_array().unwrap() {
+                let method = method.as_str().unwrap();
+                let (status, body) = call(&router, method, &uri, None).await;
+                assert_ne!(status, StatusCode::METHOD_NOT_ALLOWED, "{method} {uri}");
+                assert!(!body.contains("\"code\":\"no_route\""), "{method} {uri}");
+            }
+        }
+    }
+}
+
+fn listed(routes: &Routes) -> Vec<String> {
+    routes
+        .endpoints()
+        .iter()
+        .flat_map(|endpoint| {
+            endpoint
+                .methods
+                .iter()
+                .map(move |method| format!("{method} {}", endpoint.path))
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn optional_routes_are_listed_only_when_mounted() {
+    let engine = || TemplateEngine::new("templates/day14");
+    let uploads = day14::router_with(engine(), SecurityHeaders::default(), true);
+    assert!(listed(&uploads).contains(&"PUT /templates/:template".to_string()));
+    let no_uploads = day14::router_with(engine(), SecurityHeaders::default(), false);
+    assert!(!listed(&no_uploads)
+        .iter()
+        .any(|route| route.starts_with("PUT")));
+
+    let clock = Arc::new(TestClock::new());
+    let routes = day12::router_with(Arc::new(MemoryStore::default()), clock.clone())
+        .merge(day12::clock_admin_router(clock));
+    let routes = listed(&routes);
+    assert!(routes.contains(&"GET /packets/:packet".to_string()));
+    assert!(routes.contains(&"DELETE /packets/:packet".to_string()));
+    assert!(routes.contains(&"POST /clock/freeze".to_string()));
+}
+
+#[tokio::test]
+async fn index_lists_the_catalog() {
+    let router = registry::router(days::ALL);
+    let (status, body) = call(&router, "GET", "/", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let catalog: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(catalog.as_array().unwrap().len(), days::ALL.len());
+    assert_eq!(catalog[0]["prefix"], "/-1");
+    assert_eq!(catalog[0]["endpoints"][0]["path"], "/-1/error");
+
+    let request = Request::get("/")
+        .header(header::ACCEPT, "text/html,application/xhtml+xml")
+        .body(Body::empty())
+        .unwrap();
+    let (_, headers, _) = send(&router, request).await;
+    assert!(headers[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/html"));
+    assert!(headers.contains_key(header::CONTENT_SECURITY_POLICY));
+    assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+
+    let (status, body) = call(&router, "GET", "/nowhere", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let problem: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(problem["code"], "no_route");
+    assert_eq!(problem["detail"], "No route for /nowhere");
+}